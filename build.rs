@@ -0,0 +1,48 @@
+// 用 tests/corpus 中的样本训练内置的验证码消息分类模型，见 src/parser/otp_model.rs
+// 构建脚本只用到训练部分
+#[allow(dead_code)]
+#[path = "src/parser/otp_model.rs"]
+mod otp_model;
+
+use serde::Deserialize;
+use std::path::Path;
+use std::{env, fs};
+
+#[derive(Deserialize)]
+struct CorpusFile {
+    #[serde(default)]
+    cases: Vec<CorpusCase>,
+}
+
+#[derive(Deserialize)]
+struct CorpusCase {
+    message: String,
+    expected: Option<String>,
+}
+
+fn main() {
+    let corpus_dir = Path::new("tests/corpus");
+    println!("cargo:rerun-if-changed={}", corpus_dir.display());
+    println!("cargo:rerun-if-changed=src/parser/otp_model.rs");
+
+    let mut model = otp_model::OtpModel::default();
+    if let Ok(entries) = fs::read_dir(corpus_dir) {
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let content = fs::read_to_string(&path).unwrap();
+            let file: CorpusFile = toml::from_str(&content)
+                .unwrap_or_else(|e| panic!("invalid corpus file {}: {}", path.display(), e));
+            for case in file.cases {
+                model.add(&case.message, case.expected.is_some());
+            }
+        }
+    }
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("otp_model.toml");
+    fs::write(out, toml::to_string(&model).unwrap()).unwrap();
+}
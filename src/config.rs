@@ -1,6 +1,7 @@
 use chrono::Local;
 use rust_i18n::t;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{self, Write};
 use std::path::PathBuf;
@@ -49,6 +50,9 @@ impl Write for LogTarget {
     }
 }
 
+/// 默认的候选验证码正则，用户配置的正则无效时也会退回到它
+pub const DEFAULT_VERIFICATION_REGEX: &str = r"(?=[a-zA-Z0-9-]*[0-9])[a-zA-Z0-9-]{4,8}";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub auto_paste: bool,
@@ -60,11 +64,331 @@ pub struct Config {
     pub floating_window: bool,
     pub verification_keywords: Vec<String>,
     pub verification_regex: String,
+    /// 保留验证码原有的分组写法（如 "482-913"），用于要求输入分隔符的网站
+    #[serde(default)]
+    pub preserve_code_grouping: bool,
+    #[serde(default)]
+    pub keyword_tiers: KeywordTiers,
+    #[serde(default)]
+    pub keyword_packs: KeywordPackOptions,
+    #[serde(default)]
+    pub scoring: ScoringWeights,
+    #[serde(default)]
+    pub masking: MaskingOptions,
+    #[serde(default)]
+    pub rules: Vec<SenderRule>,
+    #[serde(default)]
+    pub sign_in_links: SignInLinkOptions,
+    #[serde(default)]
+    pub categories: CategorySwitches,
+    #[serde(default)]
+    pub classifier: ClassifierOptions,
+    #[serde(default)]
+    pub chain: ChainOptions,
+    #[serde(default)]
+    pub mailboxes: MailboxOptions,
+    /// 邮件 App 的数据目录，如 `~/Library/Mail/V10`；也可以是包含 V* 目录的上级目录。
+    /// 未设置时自动使用 `~/Library/Mail` 下版本号最大的 V* 目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_root: Option<PathBuf>,
+
+    /// verification_regex 校验失败的原因，只在内存中保留，不写回配置文件
+    #[serde(skip)]
+    pub regex_error: Option<String>,
 
     #[serde(default)]
     version: u32,
 }
 
+/// 候选码评分模型的各项权重
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ScoringWeights {
+    /// 与关键词每相距一个字符扣除的分数
+    pub distance: f64,
+    /// 验证码位于关键词之后的加分
+    pub after_keyword: f64,
+    /// 验证码位于关键词之前的加分
+    pub before_keyword: f64,
+    /// 数字占比的权重
+    pub digit_ratio: f64,
+    /// 长度接近 preferred_length 的权重
+    pub length: f64,
+    pub preferred_length: usize,
+    /// 前后紧邻 "："、"is"、"为" 等引导词的加分
+    pub punctuation: f64,
+    /// 看起来像账号、手机号等标识时的扣分
+    pub identifier_penalty: f64,
+    /// 锚定的关键词为强关键词时的加分
+    pub strong_keyword: f64,
+    /// 候选码附近出现排除词时的扣分，扣分后低于 0 的候选码会被淘汰
+    pub negative_keyword: f64,
+}
+
+/// 关键词的分层。`verification_keywords` 中未列入 `weak` 的都是强关键词。
+///
+/// 以英文字母或数字开头/结尾的关键词按完整单词匹配，"code" 不会命中 "decode"。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct KeywordTiers {
+    /// 弱关键词，含义宽泛，只在没有更好的锚点时使用
+    pub weak: Vec<String>,
+    /// 排除词：落在其中的关键词不算数，附近的候选码会被扣分
+    pub negative: Vec<String>,
+}
+
+/// 多语言关键词包，日文、韩文、德文等消息中的 "認証コード"、"인증번호"、"Bestätigungscode"
+///
+/// 可用的语言：ja、ko、de、fr、es、pt、ru、vi、th、ar。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct KeywordPackOptions {
+    /// 根据系统语言和消息使用的文字自动启用对应的关键词包
+    pub auto: bool,
+    /// 始终启用的关键词包，如 ["ja", "de"]
+    pub languages: Vec<String>,
+}
+
+impl Default for KeywordPackOptions {
+    fn default() -> Self {
+        Self {
+            auto: true,
+            languages: Vec::new(),
+        }
+    }
+}
+
+/// 针对特定发件人的提取规则，对应 config.toml 中的 `[[rules]]`
+///
+/// `sender` 对 iMessage 的 handle 或邮件的 From 头做不区分大小写的子串匹配，
+/// `brand` 匹配识别出的服务名称（【品牌】前缀、末尾的 [签名] 或邮件发件人名称）；两者都设置时需要同时满足。
+/// 其余字段只覆盖全局配置中的对应项，未设置的沿用全局配置。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct SenderRule {
+    pub name: Option<String>,
+    pub sender: Option<String>,
+    pub brand: Option<String>,
+    pub regex: Option<String>,
+    /// 使用正则中的第几个捕获组作为验证码，默认为整个匹配
+    pub group: Option<usize>,
+    pub keywords: Option<Vec<String>>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    /// 由悬浮窗中的纠正自动生成，只适用于纠正时的消息模板；提取不到验证码时继续尝试后面的规则和全局配置
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub learned: bool,
+}
+
+/// 各消息类别的开关，关闭的类别中的代码会被忽略。
+///
+/// 取件码不会自动输入，而是记入取件码历史。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CategorySwitches {
+    pub login_otp: bool,
+    pub payment_otp: bool,
+    pub pickup_code: bool,
+    pub booking_reference: bool,
+    pub coupon: bool,
+}
+
+impl Default for CategorySwitches {
+    fn default() -> Self {
+        Self {
+            login_otp: true,
+            payment_otp: true,
+            pickup_code: true,
+            booking_reference: false,
+            coupon: false,
+        }
+    }
+}
+
+/// 离线分类模型的设置，在提取候选码之前先判断消息是否为验证码消息
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ClassifierOptions {
+    pub enabled: bool,
+    /// 模型判断为验证码消息的概率低于该值时忽略整条消息
+    pub threshold: f64,
+}
+
+impl Default for ClassifierOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.2,
+        }
+    }
+}
+
+/// 提取策略链的设置，内置策略见 [`crate::parser::strategies`]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChainOptions {
+    /// 依次运行的策略名称，不在列表中的策略不会运行
+    pub order: Vec<String>,
+    /// 验证码的置信度达到该值时不再运行后面的策略
+    pub short_circuit_confidence: f32,
+}
+
+impl Default for ChainOptions {
+    fn default() -> Self {
+        Self {
+            order: vec![
+                "learned_template".to_string(),
+                "sender_rule".to_string(),
+                "email_subject".to_string(),
+                "keyword_proximity".to_string(),
+                "html_emphasis".to_string(),
+                "sign_in_link".to_string(),
+            ],
+            short_circuit_confidence: 0.9,
+        }
+    }
+}
+
+/// 监听邮件 App 中的哪些邮箱，见 [`crate::monitor::mailboxes`]
+///
+/// 邮箱用 glob 写法匹配，嵌套的邮箱用 `/` 分隔，如 `"INBOX"`、`"[Gmail]/All Mail"`、`"Archive/**"`；
+/// `*` 不跨越 `/`，`**` 可以跨越，不区分大小写。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct MailboxOptions {
+    /// 监听除已发送、草稿和废纸篓之外的所有邮箱
+    pub all: bool,
+    pub include: Vec<String>,
+    /// 优先于 `include` 和 `all`
+    pub exclude: Vec<String>,
+    /// 账户目录名（UUID）对应的名称，用于日志和 `accounts`
+    pub account_names: BTreeMap<String, String>,
+    /// 按账户覆盖上面的设置
+    pub accounts: Vec<AccountMailboxes>,
+}
+
+impl Default for MailboxOptions {
+    fn default() -> Self {
+        Self {
+            all: false,
+            include: vec!["INBOX".to_string(), "INBOX/**".to_string()],
+            exclude: Vec::new(),
+            account_names: BTreeMap::new(),
+            accounts: Vec::new(),
+        }
+    }
+}
+
+/// 单个账户的邮箱设置，未设置的项沿用 [`MailboxOptions`] 中的全局设置
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AccountMailboxes {
+    /// 账户目录名（UUID）或 `account_names` 中的名称
+    pub account: String,
+    pub all: Option<bool>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+}
+
+/// 一键登录链接的识别设置
+///
+/// 只有域名（或其子域名）在 `allowed_domains` 中的 https 链接才会被识别，
+/// 识别出的链接只提供"打开链接"操作，不会自动打开。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SignInLinkOptions {
+    pub enabled: bool,
+    pub allowed_domains: Vec<String>,
+}
+
+impl Default for SignInLinkOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowed_domains: vec![
+                "slack.com".to_string(),
+                "notion.so".to_string(),
+                "notion.com".to_string(),
+                "medium.com".to_string(),
+                "figma.com".to_string(),
+                "linear.app".to_string(),
+            ],
+        }
+    }
+}
+
+/// 提取候选码之前需要遮盖的实体类型，每一项都可以单独关闭
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct MaskingOptions {
+    pub dates: bool,
+    pub times: bool,
+    pub phone_numbers: bool,
+    pub amounts: bool,
+    pub urls: bool,
+    pub emails: bool,
+    pub order_numbers: bool,
+    /// 65 个字符以上的字母数字串，如链接中的 token
+    pub long_tokens: bool,
+}
+
+impl Default for MaskingOptions {
+    fn default() -> Self {
+        Self {
+            dates: true,
+            times: true,
+            phone_numbers: true,
+            amounts: true,
+            urls: true,
+            emails: true,
+            order_numbers: true,
+            long_tokens: true,
+        }
+    }
+}
+
+impl Default for KeywordTiers {
+    fn default() -> Self {
+        Self {
+            weak: vec![
+                "验证".to_string(),
+                "代码".to_string(),
+                "verification".to_string(),
+                "captcha".to_string(),
+                "code".to_string(),
+            ],
+            negative: vec![
+                "promo code".to_string(),
+                "coupon code".to_string(),
+                "discount code".to_string(),
+                "zip code".to_string(),
+                "discount".to_string(),
+                "优惠码".to_string(),
+                "兑换码".to_string(),
+                "邀请码".to_string(),
+                "折扣码".to_string(),
+            ],
+        }
+    }
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            distance: 0.02,
+            after_keyword: 0.5,
+            before_keyword: 0.0,
+            digit_ratio: 1.0,
+            length: 0.5,
+            preferred_length: 6,
+            punctuation: 0.6,
+            identifier_penalty: 2.0,
+            strong_keyword: 0.5,
+            negative_keyword: 3.0,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -87,7 +411,20 @@ impl Default for Config {
                 "code".to_string(),
                 "인증".to_string(),
             ],
-            verification_regex: r"(?=[a-zA-Z0-9-]*[0-9])[a-zA-Z0-9-]{4,8}".to_string(),
+            verification_regex: DEFAULT_VERIFICATION_REGEX.to_string(),
+            preserve_code_grouping: false,
+            keyword_tiers: KeywordTiers::default(),
+            keyword_packs: KeywordPackOptions::default(),
+            scoring: ScoringWeights::default(),
+            masking: MaskingOptions::default(),
+            rules: Vec::new(),
+            sign_in_links: SignInLinkOptions::default(),
+            categories: CategorySwitches::default(),
+            classifier: ClassifierOptions::default(),
+            chain: ChainOptions::default(),
+            mailboxes: MailboxOptions::default(),
+            mail_root: None,
+            regex_error: None,
             version: 1,
         }
     }
@@ -122,19 +459,45 @@ impl Config {
         let content = fs::read_to_string(&path)?;
 
         // 尝试解析当前版本，失败则尝试旧版本
-        match toml::from_str(&content) {
-            Ok(mut config) => {
-                config = Self::migrate_config(config);
-                config.save()?;
-                Ok(config)
+        let mut config = match toml::from_str(&content) {
+            Ok(config) => {
+                let config = Self::migrate_config(config);
+                // 只有迁移或补全默认值后内容有变化才写回，避免每次加载都改动文件
+                if toml::to_string_pretty(&config)? != content {
+                    config.save()?;
+                }
+                config
             }
             Err(_) => {
                 log::warn!("{}", t!("logging.migrating_legacy_config"));
                 let config = Self::migrate_legacy_config(&content)?;
                 config.save()?;
-                Ok(config)
+                config
             }
-        }
+        };
+
+        config.validate_regex();
+        Ok(config)
+    }
+
+    /// 校验 verification_regex，无效时记录本地化的错误信息。
+    ///
+    /// 这里不改写用户填写的正则，以免托盘保存配置时把它覆盖掉；
+    /// 提取器发现 `regex_error` 后会退回 [`DEFAULT_VERIFICATION_REGEX`]。
+    fn validate_regex(&mut self) {
+        self.regex_error = fancy_regex::Regex::new(&self.verification_regex)
+            .err()
+            .map(|e| {
+                log::error!(
+                    "{}",
+                    t!(
+                        "config.invalid_verification_regex",
+                        regex = self.verification_regex,
+                        error = e
+                    )
+                );
+                e.to_string()
+            });
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -190,7 +553,20 @@ impl Config {
             }),
             verification_regex: legacy
                 .verification_regex
-                .unwrap_or_else(|| r"(?=[a-zA-Z0-9-]*[0-9])[a-zA-Z0-9-]{4,8}".to_string()),
+                .unwrap_or_else(|| DEFAULT_VERIFICATION_REGEX.to_string()),
+            preserve_code_grouping: false,
+            keyword_tiers: KeywordTiers::default(),
+            keyword_packs: KeywordPackOptions::default(),
+            scoring: ScoringWeights::default(),
+            masking: MaskingOptions::default(),
+            rules: Vec::new(),
+            sign_in_links: SignInLinkOptions::default(),
+            categories: CategorySwitches::default(),
+            classifier: ClassifierOptions::default(),
+            chain: ChainOptions::default(),
+            mailboxes: MailboxOptions::default(),
+            mail_root: None,
+            regex_error: None,
             version: 1,
         })
     }
@@ -231,3 +607,25 @@ impl Config {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_regex_is_reported_but_kept() {
+        let mut config = Config {
+            verification_regex: "[0-9".to_string(),
+            ..Config::default()
+        };
+        config.validate_regex();
+
+        assert!(config.regex_error.is_some());
+        // 保留用户填写的内容，方便其在配置文件中修正
+        assert_eq!(config.verification_regex, "[0-9");
+
+        config.verification_regex = DEFAULT_VERIFICATION_REGEX.to_string();
+        config.validate_regex();
+        assert!(config.regex_error.is_none());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::ipc;
+use crate::parser::corrections::PendingCorrection;
 use crate::{clipboard::auto_paste, clipboard::press_enter, config::Config};
 use eframe::{
     App, NativeOptions,
@@ -11,30 +13,75 @@ const WINDOW_SIZE: egui::Vec2 = egui::Vec2::new(140.0, 110.0);
 const CLOSE_BUTTON_SIZE: f32 = 12.0;
 const CLOSE_BUTTON_OFFSET: egui::Vec2 = egui::Vec2::new(-4.0, -4.0);
 const CONTENT_OFFSET: egui::Vec2 = egui::Vec2::new(2.0, 2.0);
+const ALTERNATE_ROW_HEIGHT: f32 = 24.0;
+const MAX_ALTERNATES: usize = 3;
+const COUNTDOWN_ROW_HEIGHT: f32 = 18.0;
+const WRONG_CODE_ROW_HEIGHT: f32 = 18.0;
+const MAX_CORRECTION_CANDIDATES: usize = 5;
+// 消息未声明有效期时窗口的存活时间
+const DEFAULT_LIFETIME: Duration = Duration::from_secs(600);
 
 pub struct VerificationCodeApp {
     code: String,
     source: String,
+    // 备选验证码，(用途名称, 验证码)，首选错误时可以点选
+    alternates: Vec<(String, String)>,
+    // 一键登录链接，设置时窗口只提供"打开链接"按钮
+    sign_in_link: Option<String>,
     created_at: Instant,
     lifetime: Duration,
+    // 消息声明了有效期时显示倒计时，到期后窗口自动关闭
+    show_countdown: bool,
+    // 纠正需要的信息，设置时显示"验证码不对？"，可以改选消息中的其他候选码
+    correction: Option<PendingCorrection>,
+    // 正在选择正确的验证码
+    picking: bool,
     should_close: bool,
 }
 
 impl VerificationCodeApp {
-    pub fn new(code: String, source: String) -> Self {
+    pub fn new(
+        code: String,
+        source: String,
+        mut alternates: Vec<(String, String)>,
+        expires_in: Option<Duration>,
+        correction: Option<PendingCorrection>,
+    ) -> Self {
+        alternates.truncate(MAX_ALTERNATES);
         Self {
             code,
             source,
+            alternates,
+            sign_in_link: None,
             created_at: Instant::now(),
-            lifetime: Duration::from_secs(600),
+            lifetime: expires_in.unwrap_or(DEFAULT_LIFETIME),
+            show_countdown: expires_in.is_some(),
+            correction,
+            picking: false,
             should_close: false,
         }
     }
 
-    pub fn run(code: String, source: String) {
+    pub fn for_sign_in_link(url: String, source: String) -> Self {
+        Self {
+            sign_in_link: Some(url),
+            ..Self::new(String::new(), source, Vec::new(), None, None)
+        }
+    }
+
+    pub fn run(app: Self) {
+        let mut window_size =
+            WINDOW_SIZE + vec2(0.0, ALTERNATE_ROW_HEIGHT * app.alternates.len() as f32);
+        if app.show_countdown {
+            window_size.y += COUNTDOWN_ROW_HEIGHT;
+        }
+        if app.correction.is_some() {
+            window_size.y += WRONG_CODE_ROW_HEIGHT;
+        }
+
         let options = NativeOptions {
             viewport: egui::ViewportBuilder::default()
-                .with_inner_size(WINDOW_SIZE)
+                .with_inner_size(window_size)
                 .with_resizable(false)
                 .with_titlebar_shown(false)
                 .with_titlebar_buttons_shown(false)
@@ -51,7 +98,7 @@ impl VerificationCodeApp {
         eframe::run_native(
             "VerificationCode",
             options,
-            Box::new(|cc| {
+            Box::new(move |cc| {
                 let mut fonts = egui::FontDefinitions::default();
 
                 fonts.font_data.insert(
@@ -74,7 +121,7 @@ impl VerificationCodeApp {
                     .insert(0, "PingFang SC".to_owned());
 
                 cc.egui_ctx.set_fonts(fonts);
-                Ok(Box::new(Self::new(code, source)))
+                Ok(Box::new(app))
             }),
         )
         .unwrap();
@@ -139,7 +186,7 @@ impl VerificationCodeApp {
         }
     }
 
-    fn draw_content(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+    fn draw_content(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         let content_area = ui.max_rect().translate(CONTENT_OFFSET);
         let mut content_ui = ui.new_child(
             egui::UiBuilder::new()
@@ -148,10 +195,55 @@ impl VerificationCodeApp {
         );
 
         content_ui.add_space(5.0);
+
+        if let Some(url) = self.sign_in_link.clone() {
+            content_ui
+                .add(egui::Label::new(t!("floating_window.sign_in_link_found")).selectable(false));
+            let btn_response = self.custom_button(
+                &mut content_ui,
+                &format!(
+                    "{}\n{}",
+                    t!("floating_window.open_link"),
+                    t!("floating_window.from", source = self.source)
+                ),
+                content_ui.available_size().y - 2.0,
+            );
+            if btn_response.clicked() {
+                if let Err(e) = std::process::Command::new("open").arg(&url).spawn() {
+                    log::error!("Failed to open sign-in link: {}", e);
+                }
+                self.should_close = true;
+            }
+            return;
+        }
+
+        if self.picking {
+            self.draw_correction_picker(&mut content_ui);
+            return;
+        }
+
         content_ui.add(egui::Label::new(t!("floating_window.click_input_box")).selectable(false));
         content_ui
             .add(egui::Label::new(t!("floating_window.click_button_below")).selectable(false));
 
+        if self.show_countdown {
+            let remaining = self
+                .lifetime
+                .saturating_sub(self.created_at.elapsed())
+                .as_secs();
+            content_ui.add(
+                egui::Label::new(t!(
+                    "floating_window.expires_in",
+                    time = format!("{}:{:02}", remaining / 60, remaining % 60)
+                ))
+                .selectable(false),
+            );
+        }
+
+        let mut alternates_height = ALTERNATE_ROW_HEIGHT * self.alternates.len() as f32;
+        if self.correction.is_some() {
+            alternates_height += WRONG_CODE_ROW_HEIGHT;
+        }
         let btn_response = self.custom_button(
             &mut content_ui,
             &format!(
@@ -159,31 +251,108 @@ impl VerificationCodeApp {
                 t!("floating_window.code", code = self.code),
                 t!("floating_window.from", source = self.source)
             ),
+            content_ui.available_size().y - 2.0 - alternates_height,
         );
 
         if btn_response.clicked() {
-            let _ = auto_paste(true, &self.code);
-
-            if let Ok(config) = Config::load() {
-                if config.auto_enter {
-                    if let Err(e) = press_enter() {
-                        log::error!(
-                            "{}",
-                            t!("monitor.failed_to_press_enter_floating", error = e)
-                        );
-                    } else {
-                        log::info!("{}", t!("monitor.auto_pressed_enter_floating"));
-                    }
-                }
+            self.input_code(self.code.clone());
+        }
+
+        let mut clicked_alternate = None;
+        for (role, code) in &self.alternates {
+            content_ui.add_space(4.0);
+            let response = self.custom_button(
+                &mut content_ui,
+                &t!("floating_window.alternate", role = role, code = code),
+                ALTERNATE_ROW_HEIGHT - 4.0,
+            );
+            if response.clicked() {
+                clicked_alternate = Some(code.clone());
             }
+        }
+        if let Some(code) = clicked_alternate {
+            self.input_code(code);
+        }
 
-            self.should_close = true;
+        if self.correction.is_some() {
+            let response = content_ui.add(
+                egui::Button::new(egui::RichText::new(t!("floating_window.wrong_code")).small())
+                    .frame(false),
+            );
+            if response.clicked() {
+                self.picking = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(self.picker_size()));
+            }
+        }
+    }
+
+    fn picker_size(&self) -> egui::Vec2 {
+        let rows = self
+            .correction
+            .as_ref()
+            .map_or(0, |pending| pending.candidates.len())
+            .clamp(1, MAX_CORRECTION_CANDIDATES);
+        vec2(WINDOW_SIZE.x, 36.0 + ALTERNATE_ROW_HEIGHT * rows as f32)
+    }
+
+    fn draw_correction_picker(&mut self, ui: &mut egui::Ui) {
+        let Some(pending) = &self.correction else {
+            return;
+        };
+
+        ui.add(egui::Label::new(t!("floating_window.pick_correct_code")).selectable(false));
+        if pending.candidates.is_empty() {
+            ui.add(egui::Label::new(t!("floating_window.no_other_candidates")).selectable(false));
+            return;
+        }
+
+        let mut picked = None;
+        for candidate in pending.candidates.iter().take(MAX_CORRECTION_CANDIDATES) {
+            ui.add_space(4.0);
+            if self
+                .custom_button(ui, &candidate.code, ALTERNATE_ROW_HEIGHT - 4.0)
+                .clicked()
+            {
+                picked = Some(candidate.code.clone());
+            }
+        }
+        if let Some(code) = picked {
+            self.apply_correction(&code);
+            self.input_code(code);
         }
     }
 
-    fn custom_button(&self, ui: &mut egui::Ui, text: &str) -> egui::Response {
+    // 纠正由监听进程记录并生成发件人规则，悬浮窗进程不写配置文件
+    fn apply_correction(&self, code: &str) {
+        let Some(pending) = &self.correction else {
+            return;
+        };
+        log::info!("Corrected code {} -> {}", pending.code, code);
+        ipc::report_correction(code);
+    }
+
+    fn input_code(&mut self, code: String) {
+        let _ = auto_paste(true, &code);
+
+        if let Ok(config) = Config::load() {
+            if config.auto_enter {
+                if let Err(e) = press_enter() {
+                    log::error!(
+                        "{}",
+                        t!("monitor.failed_to_press_enter_floating", error = e)
+                    );
+                } else {
+                    log::info!("{}", t!("monitor.auto_pressed_enter_floating"));
+                }
+            }
+        }
+
+        self.should_close = true;
+    }
+
+    fn custom_button(&self, ui: &mut egui::Ui, text: &str, height: f32) -> egui::Response {
         let available_size = ui.available_size();
-        let button_size = vec2(available_size.x - 5.0, available_size.y - 2.0);
+        let button_size = vec2(available_size.x - 5.0, height);
 
         let (rect, response) = ui.allocate_exact_size(button_size, egui::Sense::click());
 
@@ -250,6 +419,9 @@ impl App for VerificationCodeApp {
             return;
         }
 
+        // 没有输入事件时也要按时刷新，保证倒计时走动、到期后及时关闭
+        ctx.request_repaint_after(Duration::from_secs(1));
+
         egui::CentralPanel::default().show(ctx, |ui| {
             self.handle_window_drag(ui, ctx);
             self.draw_close_button(ui, ctx);
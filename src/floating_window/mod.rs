@@ -1,11 +1,23 @@
 mod app;
 
-use crate::ipc;
+use crate::ipc::{self, FloatingWindowArgs};
+use app::VerificationCodeApp;
 
 pub fn maybe_start_floating_window() -> bool {
-    if let Some((code, source)) = ipc::parse_args() {
-        app::VerificationCodeApp::run(code, source);
-        return true;
+    match ipc::parse_args() {
+        Some(FloatingWindowArgs::Code {
+            code,
+            source,
+            alternates,
+            expires_in,
+            correction,
+        }) => VerificationCodeApp::run(VerificationCodeApp::new(
+            code, source, alternates, expires_in, correction,
+        )),
+        Some(FloatingWindowArgs::SignInLink { url, source }) => {
+            VerificationCodeApp::run(VerificationCodeApp::for_sign_in_link(url, source))
+        }
+        None => return false,
     }
-    false
+    true
 }
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// 历史记录最多保留的条数，超出时丢弃最早的记录
+const MAX_ENTRIES: usize = 50;
+
+/// 一条取件码记录
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PickupEntry {
+    pub code: String,
+    pub service: Option<String>,
+    /// 收到时间，RFC 3339 格式
+    pub received_at: String,
+    /// 消息声明的过期时间，RFC 3339 格式
+    pub expires_at: Option<String>,
+}
+
+impl PickupEntry {
+    pub fn new(
+        code: &str,
+        service: Option<&str>,
+        validity: Option<Duration>,
+        now: DateTime<Local>,
+    ) -> Self {
+        let expires_at = validity
+            .and_then(|validity| chrono::Duration::from_std(validity).ok())
+            .map(|validity| (now + validity).to_rfc3339());
+        Self {
+            code: code.to_string(),
+            service: service.map(str::to_string),
+            received_at: now.to_rfc3339(),
+            expires_at,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Local>) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|expires_at| DateTime::parse_from_rfc3339(expires_at).ok())
+            .is_some_and(|expires_at| expires_at < now)
+    }
+}
+
+/// 取件码等不自动输入的代码的历史记录，保存在配置目录下的 pickup_codes.toml
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct PickupHistory {
+    #[serde(default)]
+    pub entries: Vec<PickupEntry>,
+}
+
+impl PickupHistory {
+    pub fn get_history_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_default()
+            .join("messauto")
+            .join("pickup_codes.toml")
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 添加一条记录，同时清理已过期的记录
+    pub fn push(&mut self, entry: PickupEntry, now: DateTime<Local>) {
+        self.entries.retain(|e| !e.is_expired(now));
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// 尚未过期的记录，最新的在前
+    pub fn active(&self, now: DateTime<Local>) -> Vec<&PickupEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|e| !e.is_expired(now))
+            .collect()
+    }
+}
+
+/// 把取件码记入历史文件，返回尚未过期的记录数
+pub fn record_pickup_code(
+    code: &str,
+    service: Option<&str>,
+    validity: Option<Duration>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let path = PickupHistory::get_history_path();
+    let now = Local::now();
+    let mut history = PickupHistory::load_from(&path)?;
+    history.push(PickupEntry::new(code, service, validity, now), now);
+    history.save_to(&path)?;
+    Ok(history.active(now).len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_drops_expired_entries_and_round_trips() {
+        let now = Local::now();
+        let earlier = now - chrono::Duration::hours(1);
+
+        let mut history = PickupHistory::default();
+        history.push(
+            PickupEntry::new(
+                "3-2-1045",
+                Some("菜鸟驿站"),
+                Some(Duration::from_secs(600)),
+                earlier,
+            ),
+            earlier,
+        );
+        history.push(
+            PickupEntry::new("12345678", Some("丰巢"), None, earlier),
+            earlier,
+        );
+        assert_eq!(history.active(now).len(), 1);
+        assert_eq!(history.active(now)[0].code, "12345678");
+
+        history.push(PickupEntry::new("88-6-2001", None, None, now), now);
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.active(now)[0].code, "88-6-2001");
+
+        let path =
+            std::env::temp_dir().join(format!("messauto-history-{}.toml", std::process::id()));
+        history.save_to(&path).unwrap();
+        let loaded = PickupHistory::load_from(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded, history);
+    }
+}
@@ -1,17 +1,168 @@
-pub fn parse_args() -> Option<(String, String)> {
+use crate::parser::corrections::{self, PendingCorrection};
+use crate::parser::{LabeledCode, SharedExtractor};
+use rust_i18n::t;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Stdio};
+use std::time::Duration;
+
+// 悬浮窗进程在标准输出中报告用户改选的验证码时使用的前缀，其他输出被忽略
+const CORRECTION_PREFIX: &str = "messauto-correction:";
+
+/// 悬浮窗进程的启动参数
+pub enum FloatingWindowArgs {
+    Code {
+        code: String,
+        source: String,
+        /// 备选验证码，(用途名称, 验证码)
+        alternates: Vec<(String, String)>,
+        /// 消息中声明的有效期
+        expires_in: Option<Duration>,
+        /// 纠正需要的信息，由监听进程通过标准输入传入
+        correction: Option<PendingCorrection>,
+    },
+    SignInLink {
+        url: String,
+        source: String,
+    },
+}
+
+pub fn parse_args() -> Option<FloatingWindowArgs> {
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() >= 4 && args[1] == "--floating-window" {
-        Some((args[2].clone(), args[3].clone()))
+        // 验证码和来源之后是可选的 --expires-in <秒数> 和 --correction，再之后是成对的 用途名称 + 备选验证码
+        let mut rest = &args[4..];
+        let mut expires_in = None;
+        if rest.len() >= 2 && rest[0] == "--expires-in" {
+            expires_in = rest[1].parse().ok().map(Duration::from_secs);
+            rest = &rest[2..];
+        }
+        let mut correction = None;
+        if !rest.is_empty() && rest[0] == "--correction" {
+            correction = read_pending_correction();
+            rest = &rest[1..];
+        }
+        let alternates = rest
+            .chunks_exact(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Some(FloatingWindowArgs::Code {
+            code: args[2].clone(),
+            source: args[3].clone(),
+            alternates,
+            expires_in,
+            correction,
+        })
+    } else if args.len() >= 4 && args[1] == "--sign-in-link" {
+        Some(FloatingWindowArgs::SignInLink {
+            url: args[2].clone(),
+            source: args[3].clone(),
+        })
     } else {
         None
     }
 }
 
-pub fn spawn_floating_window(code: &str, source: &str) -> std::io::Result<std::process::Child> {
+// 悬浮窗进程中读取监听进程写入标准输入的纠正信息
+fn read_pending_correction() -> Option<PendingCorrection> {
+    let mut content = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut content) {
+        log::warn!("Failed to read pending correction: {}", e);
+        return None;
+    }
+    toml::from_str(&content)
+        .map_err(|e| log::warn!("Failed to parse pending correction: {}", e))
+        .ok()
+}
+
+/// 启动验证码悬浮窗。传入 `correction` 时悬浮窗显示"验证码不对？"，
+/// 纠正信息通过子进程的标准输入传递，不写入磁盘；用户的选择由 [`learn_from_floating_window`] 读取
+pub fn spawn_floating_window(
+    code: &str,
+    source: &str,
+    alternates: &[LabeledCode],
+    expires_in: Option<Duration>,
+    correction: Option<&PendingCorrection>,
+) -> std::io::Result<Child> {
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command.arg("--floating-window").arg(code).arg(source);
+    if let Some(expires_in) = expires_in {
+        command
+            .arg("--expires-in")
+            .arg(expires_in.as_secs().to_string());
+    }
+    if correction.is_some() {
+        command
+            .arg("--correction")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped());
+    }
+    for alternate in alternates {
+        command.arg(alternate.role.label()).arg(&alternate.code);
+    }
+
+    let mut child = command.spawn()?;
+    if let (Some(correction), Some(mut stdin)) = (correction, child.stdin.take()) {
+        let content = toml::to_string(correction).map_err(std::io::Error::other)?;
+        // 写完后关闭标准输入，悬浮窗进程读到结尾
+        stdin.write_all(content.as_bytes())?;
+    }
+    Ok(child)
+}
+
+/// 悬浮窗进程中报告用户改选的验证码，由监听进程记录纠正
+pub fn report_correction(code: &str) {
+    let mut stdout = std::io::stdout();
+    let _ = writeln!(stdout, "{}{}", CORRECTION_PREFIX, code);
+    let _ = stdout.flush();
+}
+
+/// 在后台等待悬浮窗进程退出。用户在悬浮窗中改选了验证码时，在监听进程中记录纠正并学习规则，
+/// 配置只由监听进程写入，不会与提取器的重新加载相互竞争
+pub fn learn_from_floating_window(
+    mut child: Child,
+    pending: PendingCorrection,
+    extractor: SharedExtractor,
+) {
+    std::thread::spawn(move || {
+        // 读到悬浮窗进程退出为止，避免其后续输出因管道关闭而失败
+        let corrected = child.stdout.take().and_then(|stdout| {
+            BufReader::new(stdout)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| line.strip_prefix(CORRECTION_PREFIX).map(str::to_string))
+                .last()
+        });
+        let _ = child.wait();
+
+        let Some(code) = corrected else {
+            return;
+        };
+        match corrections::apply_correction(&extractor, &pending, &code) {
+            Ok(Some(rule)) => log::info!(
+                "{}",
+                t!(
+                    "floating_window.learned_rule",
+                    rule = rule.name.unwrap_or_default()
+                )
+            ),
+            Ok(None) => log::info!(
+                "Recorded correction {} -> {} without a rule",
+                pending.code,
+                code
+            ),
+            Err(e) => log::error!(
+                "{}",
+                t!("floating_window.failed_to_apply_correction", error = e)
+            ),
+        }
+    });
+}
+
+pub fn spawn_sign_in_link_window(url: &str, source: &str) -> std::io::Result<std::process::Child> {
     std::process::Command::new(std::env::current_exe()?)
-        .arg("--floating-window")
-        .arg(code)
+        .arg("--sign-in-link")
+        .arg(url)
         .arg(source)
         .spawn()
 }
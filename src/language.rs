@@ -3,6 +3,11 @@ use sys_locale::get_locale;
 /// Available locales in the application
 const AVAILABLE_LOCALES: &[&str] = &["en", "zh-CN"];
 
+/// Returns the raw system locale, e.g. "ja-JP"
+pub fn system_locale() -> Option<String> {
+    get_locale()
+}
+
 /// Detects the system locale and matches it to available locales
 pub fn detect_system_locale() -> String {
     match get_locale() {
@@ -1,6 +1,7 @@
 mod clipboard;
 mod config;
 mod floating_window;
+mod history;
 mod ipc;
 mod language;
 mod launch;
@@ -23,10 +24,23 @@ use tokio::runtime::Runtime;
 rust_i18n::i18n!("./locales");
 
 fn main() {
+    // 回归语料检查不受系统语言影响，在启用系统语言的关键词包之前运行
+    if corpus_check_requested() {
+        return;
+    }
+
     let system_locale = language::detect_system_locale();
     rust_i18n::set_locale(&system_locale);
+    // 系统语言对应的验证码关键词包（如日文的 "認証コード"）自动启用
+    if let Some(locale) = language::system_locale() {
+        parser::keyword_packs::set_system_language(&locale);
+    }
     println!("=== {} ===", t!("app.name"));
 
+    if corpus_add_requested() || train_classifier_requested() {
+        return;
+    }
+
     if let Err(e) = config::Config::init_logging() {
         eprintln!("Failed to initialize logging: {}", e);
         env_logger::Builder::from_default_env()
@@ -61,7 +75,7 @@ fn main() {
     if test_mode {
         sleep(Duration::from_secs(2));
         info!("{}", t!("monitor.starting_test_verification_window"));
-        if let Ok(child) = ipc::spawn_floating_window("123456", "Test") {
+        if let Ok(child) = ipc::spawn_floating_window("123456", "Test", &[], None, None) {
             info!(
                 "{}",
                 t!(
@@ -72,7 +86,7 @@ fn main() {
 
             thread::sleep(Duration::from_secs(5));
 
-            if let Ok(child2) = ipc::spawn_floating_window("654321", "Test") {
+            if let Ok(child2) = ipc::spawn_floating_window("654321", "Test", &[], None, None) {
                 info!(
                     "{}",
                     t!(
@@ -119,3 +133,138 @@ fn main() {
 
     info!("{}", t!("monitor.application_exited"));
 }
+
+// 开发辅助：把日志中的一条消息追加到回归语料
+//
+// messauto --corpus-add "<日志行>" [--expected <验证码|none>] [--sender <发件人>]
+//          [--notes <备注>] [--corpus-dir <目录，默认 tests/corpus>]
+fn corpus_add_requested() -> bool {
+    let args: Vec<String> = env::args().collect();
+    let Some(pos) = args.iter().position(|arg| arg == "--corpus-add") else {
+        return false;
+    };
+    let Some(line) = args.get(pos + 1) else {
+        eprintln!(
+            "Usage: messauto --corpus-add \"<log line>\" [--expected <code|none>] [--sender <sender>] [--notes <notes>] [--corpus-dir <dir>]"
+        );
+        return true;
+    };
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+
+    let message = parser::corpus::message_from_log_line(line);
+    let sender = option("--sender");
+    // 未指定期望结果时使用当前提取结果，追加后请人工核对
+    let expected = match option("--expected") {
+        Some(code) if code.eq_ignore_ascii_case("none") => None,
+        Some(code) => Some(code),
+        None => {
+            let config = config::Config::load().unwrap_or_default();
+            parser::ExtractorChain::new(&config)
+                .extract(&message, sender.as_deref())
+                .map(|result| result.code)
+        }
+    };
+    let case = parser::corpus::CorpusCase {
+        language: parser::corpus::detect_language(&message).to_string(),
+        message,
+        sender,
+        expected,
+        notes: option("--notes"),
+        known_failure: false,
+    };
+
+    let dir = option("--corpus-dir").unwrap_or_else(|| "tests/corpus".to_string());
+    let dir = std::path::Path::new(&dir);
+    match parser::corpus::append_case(dir, &case) {
+        Ok(path) => println!(
+            "Added corpus case to {} (expected: {:?})",
+            path.display(),
+            case.expected
+        ),
+        Err(e) => {
+            eprintln!("Failed to add corpus case: {}", e);
+            return true;
+        }
+    }
+
+    // 用当前配置重新跑一遍语料，报告各语言的正确率
+    match parser::corpus::load_dir(dir) {
+        Ok(cases) => {
+            let config = config::Config::load().unwrap_or_default();
+            let extractor = parser::ExtractorChain::new(&config);
+            let outcomes = parser::corpus::run(&extractor, &cases);
+            for (language, stats) in parser::corpus::stats_by_language(&outcomes) {
+                println!(
+                    "{}: {}/{} ({:.2}%)",
+                    language,
+                    stats.passed,
+                    stats.total,
+                    stats.accuracy() * 100.0
+                );
+            }
+        }
+        Err(e) => eprintln!("Failed to load corpus: {}", e),
+    }
+    true
+}
+
+// 用默认配置运行回归语料，有样本回归时以状态码 1 退出，见 tests/corpus.rs
+//
+// messauto --corpus-check [语料目录，默认 tests/corpus]
+fn corpus_check_requested() -> bool {
+    let args: Vec<String> = env::args().collect();
+    let Some(pos) = args.iter().position(|arg| arg == "--corpus-check") else {
+        return false;
+    };
+    let dir = args.get(pos + 1).map_or("tests/corpus", String::as_str);
+
+    let cases = match parser::corpus::load_dir(std::path::Path::new(dir)) {
+        Ok(cases) if !cases.is_empty() => cases,
+        Ok(_) => {
+            eprintln!("Corpus is empty: {}", dir);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to load corpus: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let extractor = parser::ExtractorChain::new(&config::Config::default());
+    let outcomes = parser::corpus::run(&extractor, &cases);
+    let regressions = parser::corpus::report(&outcomes);
+    if regressions > 0 {
+        eprintln!("{} corpus cases regressed", regressions);
+        std::process::exit(1);
+    }
+    true
+}
+
+// 用纠正过的样本重新训练验证码消息分类模型
+//
+// messauto --train-classifier [样本目录，默认为配置目录下的 corrections]
+fn train_classifier_requested() -> bool {
+    let args: Vec<String> = env::args().collect();
+    let Some(pos) = args.iter().position(|arg| arg == "--train-classifier") else {
+        return false;
+    };
+    let dir = args
+        .get(pos + 1)
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(parser::classifier::get_corrections_dir);
+
+    match parser::classifier::retrain(&dir) {
+        Ok((path, count)) => println!(
+            "Trained OTP classifier with {} corrected cases from {}, saved to {}",
+            count,
+            dir.display(),
+            path.display()
+        ),
+        Err(e) => eprintln!("Failed to train OTP classifier: {}", e),
+    }
+    true
+}
@@ -2,19 +2,22 @@ use super::{
     commands::MonitorCommand, email::EmailProcessor, message::MessageProcessor,
     watcher::FileWatcher,
 };
+use crate::parser::SharedExtractor;
 use rust_i18n::t;
 use tokio::sync::mpsc::Receiver;
 
 pub struct MonitorActor {
     receiver: Receiver<MonitorCommand>,
+    extractor: SharedExtractor,
     message_watcher: Option<FileWatcher<MessageProcessor>>,
     email_watcher: Option<FileWatcher<EmailProcessor>>,
 }
 
 impl MonitorActor {
-    pub fn new(receiver: Receiver<MonitorCommand>) -> Self {
+    pub fn new(receiver: Receiver<MonitorCommand>, extractor: SharedExtractor) -> Self {
         Self {
             receiver,
+            extractor,
             message_watcher: None,
             email_watcher: None,
         }
@@ -36,7 +39,7 @@ impl MonitorActor {
                     return;
                 }
                 log::info!("{}", t!("actor.starting_message_monitoring"));
-                let mut watcher = FileWatcher::new(MessageProcessor::new());
+                let mut watcher = FileWatcher::new(MessageProcessor::new(self.extractor.clone()));
                 if let Err(e) = watcher.start() {
                     log::error!("{}", t!("actor.failed_to_start_message_watcher", error = e));
                 } else {
@@ -59,7 +62,7 @@ impl MonitorActor {
                     return;
                 }
                 log::info!("{}", t!("actor.starting_email_monitoring"));
-                let mut watcher = FileWatcher::new(EmailProcessor::new());
+                let mut watcher = FileWatcher::new(EmailProcessor::new(self.extractor.clone()));
                 if let Err(e) = watcher.start() {
                     log::error!("{}", t!("actor.failed_to_start_email_watcher", error = e));
                 } else {
@@ -94,6 +97,13 @@ impl MonitorActor {
                         "Stopped"
                     }
                 ));
+                if let Some(path) = self
+                    .email_watcher
+                    .as_ref()
+                    .and_then(|w| w.current_watch_path())
+                {
+                    status.push_str(&format!("\n- Mail Data Directory: {}", path.display()));
+                }
                 let _ = responder.send(status);
             }
         }
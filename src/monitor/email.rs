@@ -1,30 +1,105 @@
-use email::MimeMessage;
 use log::warn;
 use log::{debug, error, info};
 use notify::{EventKind, RecursiveMode};
 use rust_i18n::t;
+use std::collections::VecDeque;
 use std::env;
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
+use super::html;
+use super::mailboxes;
+use super::mime::{self, Mailbox};
 use super::watcher::FileProcessor;
 use crate::clipboard;
-use crate::config::Config;
+use crate::history;
 use crate::ipc;
-use crate::parser;
+use crate::parser::corrections::PendingCorrection;
+use crate::parser::{Extraction, Message, SharedExtractor};
+use crate::tray;
 
 rust_i18n::i18n!("../locales");
 
+// 记住最近处理过的邮件数量，用于跳过出现在多个邮箱中的同一封邮件
+const RECENT_MESSAGE_IDS: usize = 64;
+
 #[derive(Clone)]
-pub struct EmailProcessor;
+pub struct EmailProcessor {
+    extractor: SharedExtractor,
+    // 启动时确定的邮件 App 数据目录，见 [`mailboxes::resolve_mail_root`]
+    mail_root: PathBuf,
+    // 最近处理过的邮件的 Message-ID，Gmail 的收件箱和"所有邮件"中会各有一份
+    recent_message_ids: Arc<Mutex<VecDeque<String>>>,
+}
+
+// 从 .emlx 中解析出的、提取验证码需要的内容
+struct ParsedEmail {
+    body: String,
+    // 解码后的 From 头，地址用于匹配发件人规则，显示名称用于识别服务
+    from: Option<Mailbox>,
+    // 解码后的主题，不少服务把验证码直接写在主题中
+    subject: Option<String>,
+    // HTML 正文中加粗、大号或等宽字体的短文本
+    emphasized: Vec<String>,
+    message_id: Option<String>,
+}
 
 impl EmailProcessor {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(extractor: SharedExtractor) -> Self {
+        let current = extractor.current();
+        let config = current.config();
+        let home_dir = env::var("HOME").expect("Failed to get HOME directory");
+        let mail_root = mailboxes::resolve_mail_root(
+            config.mail_root.as_deref(),
+            &PathBuf::from(&home_dir).join("Library/Mail"),
+        );
+        info!(
+            "{}",
+            t!(
+                "monitor.mail_data_directory",
+                path = format!("{:?}", mail_root)
+            )
+        );
+
+        // 列出账户目录，方便在配置中为它们命名
+        let options = &config.mailboxes;
+        for account in mailboxes::list_accounts(&mail_root) {
+            info!(
+                "{}",
+                t!(
+                    "monitor.mail_account",
+                    account = account,
+                    name = mailboxes::account_name(options, &account)
+                )
+            );
+        }
+
+        Self {
+            extractor,
+            mail_root,
+            recent_message_ids: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    // 记录邮件的 Message-ID，已经处理过时返回 false
+    fn remember_message_id(&self, id: &str) -> bool {
+        let mut recent = self.recent_message_ids.lock().unwrap();
+        if recent.iter().any(|seen| seen == id) {
+            return false;
+        }
+        recent.push_back(id.to_string());
+        if recent.len() > RECENT_MESSAGE_IDS {
+            recent.pop_front();
+        }
+        true
     }
 
-    fn read_emlx(&self, path: &Path) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    fn read_emlx(
+        &self,
+        path: &Path,
+    ) -> Result<ParsedEmail, Box<dyn std::error::Error + Send + Sync>> {
         let mut file = fs::File::open(path)?;
         let mut buffer = Vec::new();
 
@@ -32,121 +107,64 @@ impl EmailProcessor {
 
         let message = emlx::parse_emlx(&buffer)?;
 
-        let raw_content = String::from_utf8(message.message.to_vec())?;
+        // 按字节解析 MIME，各部分按声明的字符集解码，GBK、Big5 等编码的邮件也能读取
+        let mime_message = mime::parse(message.message);
 
-        let mime_message = MimeMessage::parse(&raw_content)?;
+        // 有 HTML 部分时转换为纯文本，没有 text/plain 部分时以它为正文
+        let html = mime_message.html().map(|html| html::to_text(&html));
+        let (html_text, emphasized) = match html {
+            Some(converted) => (Some(converted.text), converted.emphasized),
+            None => (None, Vec::new()),
+        };
 
-        // 尝试提取纯文本内容
-        let body_content = match extract_plain_text_content(&mime_message) {
+        let body_content = match mime_message
+            .plain_text()
+            .or(html_text.filter(|text| !text.is_empty()))
+        {
             Some(plain_text) => {
                 info!("{}", t!("monitor.mail_content", content = &plain_text));
                 plain_text
             }
             None => {
-                // 没有找到 text/plain 部分，返回错误
+                // 既没有 text/plain 部分也没有 text/html 部分，返回错误
                 warn!("{}", t!("monitor.no_plain_text_found"));
-                return Err("No plain text content found in email".into());
+                return Err("No text content found in email".into());
             }
         };
 
+        let subject = mime_message
+            .header("Subject")
+            .map(str::trim)
+            .filter(|subject| !subject.is_empty())
+            .map(str::to_string);
         debug!(
             "{}",
             t!(
                 "monitor.email_subject",
-                subject = format!("{:?}", mime_message.headers.get("Subject".to_string()))
+                subject = subject.as_deref().unwrap_or_default()
             )
         );
         debug!("Extracted plain text length: {}", body_content.len());
 
-        Ok(body_content)
-    }
-}
-
-fn extract_plain_text_content(mime_message: &MimeMessage) -> Option<String> {
-    // 策略1: 检查是否有解析好的子部分
-    if !mime_message.children.is_empty() {
-        for child in &mime_message.children {
-            if let Some(content_type) = child.headers.get("Content-Type".to_string()) {
-                if content_type
-                    .to_string()
-                    .to_lowercase()
-                    .contains("text/plain")
-                {
-                    if let Ok(decoded) = child.decoded_body_string() {
-                        let cleaned = decoded.trim().to_string();
-                        if !cleaned.is_empty() {
-                            return Some(cleaned);
-                        }
-                    }
-                }
-            }
-        }
-        // 如果有子部分但没有找到 text/plain，返回 None
-        return None;
-    }
-
-    // 策略2: 如果没有子部分，尝试手动解析多部分内容
-    let raw_content = mime_message
-        .decoded_body_string()
-        .unwrap_or_else(|_| mime_message.body.clone());
-
-    extract_plain_text_from_multipart(&raw_content)
-}
-
-fn extract_plain_text_from_multipart(content: &str) -> Option<String> {
-    let lines: Vec<&str> = content.lines().collect();
-    let mut result = Vec::new();
-    let mut in_plain_text_section = false;
-    let mut reading_headers = false;
-    let mut found_plain_text_type = false;
-
-    for line in lines {
-        // 检测 MIME 边界
-        if line.starts_with("------") {
-            in_plain_text_section = false;
-            reading_headers = true;
-            found_plain_text_type = false;
-            continue;
-        }
-
-        // 处理头部信息
-        if reading_headers {
-            // 空行表示头部结束
-            if line.trim().is_empty() {
-                reading_headers = false;
-                in_plain_text_section = found_plain_text_type;
-                continue;
-            }
-
-            // 检查是否是 text/plain 类型（只检查 Content-Type 头）
-            if line.to_lowercase().starts_with("content-type:")
-                && line.to_lowercase().contains("text/plain")
-            {
-                found_plain_text_type = true;
-            }
-            continue;
-        }
-
-        // 收集 text/plain 内容
-        if in_plain_text_section {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                result.push(trimmed);
-            }
-        }
-    }
-
-    if result.is_empty() {
-        None
-    } else {
-        Some(result.join(" "))
+        let from = mime_message.header("From").and_then(Mailbox::parse);
+        let message_id = mime_message
+            .header("Message-ID")
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+
+        Ok(ParsedEmail {
+            body: body_content,
+            from,
+            subject,
+            emphasized,
+            message_id,
+        })
     }
 }
 
 impl FileProcessor for EmailProcessor {
     fn get_watch_path(&self) -> PathBuf {
-        let home_dir = env::var("HOME").expect("Failed to get HOME directory");
-        PathBuf::from(&home_dir).join("Library/Mail/V10")
+        self.mail_root.clone()
     }
 
     fn get_file_pattern(&self) -> &str {
@@ -166,37 +184,153 @@ impl FileProcessor for EmailProcessor {
             return Ok(());
         }
 
-        if !path.to_str().unwrap().contains("INBOX.mbox") {
+        let extractor = self.extractor.current();
+        let config = extractor.config();
+
+        let Some(location) = mailboxes::locate(&self.mail_root, path) else {
+            return Ok(());
+        };
+        let account = mailboxes::account_name(&config.mailboxes, &location.account);
+        if !mailboxes::is_watched(&config.mailboxes, &location) {
+            debug!(
+                "{}",
+                t!(
+                    "monitor.skipped_mailbox",
+                    account = account,
+                    mailbox = location.mailbox
+                )
+            );
             return Ok(());
         }
 
         debug!(
             "{}",
-            t!("monitor.new_email_created", path = format!("{:?}", &path))
+            t!(
+                "monitor.new_email_created",
+                account = account,
+                mailbox = location.mailbox,
+                path = format!("{:?}", &path)
+            )
         );
 
-        let content = match self.read_emlx(Path::new(&path.to_string_lossy().replace(".tmp", ""))) {
-            Ok(content) => content,
+        let email = match self.read_emlx(Path::new(&path.to_string_lossy().replace(".tmp", ""))) {
+            Ok(email) => email,
             Err(e) => {
                 debug!("Failed to extract plain text from email: {}", e);
                 return Ok(()); // 跳过这个邮件，不是错误
             }
         };
 
+        let duplicate = email
+            .message_id
+            .as_deref()
+            .is_some_and(|id| !self.remember_message_id(id));
+        if duplicate {
+            debug!(
+                "{}",
+                t!(
+                    "monitor.duplicate_email",
+                    id = email.message_id.unwrap_or_default()
+                )
+            );
+            return Ok(());
+        }
+
+        // 主题放在正文前一起提取，验证码的位置对整条消息都有效
+        let content = match &email.subject {
+            Some(subject) => format!("{}\n{}", subject, email.body),
+            None => email.body,
+        };
         debug!("{}", t!("monitor.email_content", content = content));
+        if let Some(from) = &email.from {
+            debug!("{}", t!("monitor.email_from", from = from));
+        }
 
-        if let Some(code) = parser::extract_verification_code(&content) {
+        let sender = email.from.as_ref().map(Mailbox::to_string);
+        let mut message =
+            Message::new(&content, sender.as_deref()).with_emphasized(&email.emphasized);
+        if let Some(subject) = &email.subject {
+            message = message.with_subject(subject);
+        }
+        // 整条链只运行一次，没有验证码时才使用检测到的登录链接
+        let extraction = extractor.run_message(&message);
+        if let Some(Extraction::Code(result)) = extraction {
+            let code = result.code.clone();
             info!(
                 "{}",
                 t!("monitor.found_verification_code_email", code = code)
             );
+            info!(
+                "{}",
+                t!(
+                    "monitor.extraction_details",
+                    keyword = result.keyword,
+                    distance = result.distance,
+                    confidence = format!("{:.2}", result.confidence)
+                )
+            );
+            if let Some(rule) = &result.rule {
+                info!("{}", t!("monitor.matched_sender_rule", rule = rule));
+            }
+            if let Some(service) = &result.service {
+                info!("{}", t!("monitor.identified_service", service = service));
+            }
+            for candidate in &result.rejected {
+                debug!(
+                    "{}",
+                    t!(
+                        "monitor.rejected_candidate",
+                        code = candidate.code,
+                        span = format!("{:?}", candidate.span),
+                        reason = candidate.reason
+                    )
+                );
+            }
+            for alternate in &result.alternates {
+                info!(
+                    "{}",
+                    t!(
+                        "monitor.alternate_code",
+                        code = alternate.code,
+                        role = alternate.role.label(),
+                        confidence = format!("{:.2}", alternate.confidence)
+                    )
+                );
+            }
             info!("{}", t!("monitor.mail_content", content = &content));
 
-            let config = Config::load().unwrap_or_default();
+            // 取件码等非验证码不自动输入，只记入历史
+            if !result.category.is_otp() {
+                match history::record_pickup_code(&code, result.service.as_deref(), result.validity)
+                {
+                    Ok(active) => info!(
+                        "{}",
+                        t!("monitor.recorded_pickup_code", code = code, count = active)
+                    ),
+                    Err(e) => error!("{}", t!("monitor.failed_to_record_pickup_code", error = e)),
+                }
+                return Ok(());
+            }
 
             if config.floating_window {
-                match ipc::spawn_floating_window(&code, "Mail") {
-                    Ok(_) => debug!("Floating window spawned successfully"),
+                // 纠正需要的信息随悬浮窗进程的标准输入传入，验证码不对时可以在悬浮窗中改选
+                let correction =
+                    PendingCorrection::from_result(&content, sender.as_deref(), &result);
+                match ipc::spawn_floating_window(
+                    &code,
+                    result
+                        .service
+                        .as_deref()
+                        .or(email.from.as_ref().map(Mailbox::display_name))
+                        .unwrap_or("Mail"),
+                    &result.alternates,
+                    result.validity,
+                    Some(&correction),
+                ) {
+                    Ok(child) => {
+                        debug!("Floating window spawned successfully");
+                        ipc::learn_from_floating_window(child, correction, self.extractor.clone());
+                    }
                     Err(e) => error!("Failed to spawn floating window: {}", e),
                 }
             } else {
@@ -255,6 +389,22 @@ impl FileProcessor for EmailProcessor {
                     }
                 }
             }
+        } else if let Some(Extraction::SignInLink(link)) = extraction {
+            info!(
+                "{}",
+                t!(
+                    "monitor.found_sign_in_link_email",
+                    keyword = link.keyword,
+                    url = link.url
+                )
+            );
+            tray::set_sign_in_link(&link.url);
+            if config.floating_window {
+                match ipc::spawn_sign_in_link_window(&link.url, "Mail") {
+                    Ok(_) => debug!("Sign-in link window spawned successfully"),
+                    Err(e) => error!("Failed to spawn sign-in link window: {}", e),
+                }
+            }
         } else {
             debug!("{}", t!("monitor.no_verification_code_email"));
         }
@@ -0,0 +1,448 @@
+//! 只有 HTML 正文的邮件转换为纯文本，供验证码提取使用
+
+// 加粗、大号或等宽的文字超过这个长度时不再视为验证码所在的元素
+const MAX_EMPHASIZED_CHARS: usize = 40;
+
+// 内容整体丢弃的元素
+const SKIPPED: &[&str] = &["script", "style", "head", "title", "template", "noscript"];
+
+// 前后换行的块级元素
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "br",
+    "center",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tbody",
+    "thead",
+    "tr",
+    "ul",
+];
+
+// 没有结束标签的元素
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "hr", "img", "input", "link", "meta", "source", "wbr",
+];
+
+// 本身就表示强调或等宽的元素
+const EMPHASIS: &[&str] = &[
+    "b", "big", "code", "h1", "h2", "h3", "kbd", "pre", "samp", "strong", "tt",
+];
+
+const MONOSPACE_FONTS: &[&str] = &["monospace", "courier", "consolas", "menlo", "monaco"];
+
+/// HTML 转换后的文本
+#[derive(Debug, Default, PartialEq)]
+pub struct HtmlText {
+    /// 按块级元素分行的纯文本，脚本和样式已去掉，实体已解码
+    pub text: String,
+    /// 加粗、大号字体或等宽字体的短文本，验证码常放在这类元素中
+    pub emphasized: Vec<String>,
+}
+
+/// 把 HTML 转换为纯文本。容忍未闭合的标签和不规范的嵌套，不会因为输入格式错误而失败
+pub fn to_text(html: &str) -> HtmlText {
+    let mut text = String::new();
+    let mut emphasized = Vec::new();
+    // 打开的元素名称，以及最外层强调元素在栈中的位置和已收集的文字
+    let mut stack: Vec<String> = Vec::new();
+    let mut emphasis: Option<(usize, String)> = None;
+
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            push_text(&mut text, &mut emphasis, rest, in_pre(&stack));
+            break;
+        };
+        push_text(&mut text, &mut emphasis, &rest[..open], in_pre(&stack));
+        rest = &rest[open..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(tag) = parse_tag(rest) else {
+            // 不是标签的 '<' 按文字处理
+            push_text(&mut text, &mut emphasis, "<", in_pre(&stack));
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[tag.length..];
+        if tag.name.is_empty() {
+            // <!DOCTYPE>、<?xml ?> 等声明
+            continue;
+        }
+
+        let name = tag.name.as_str();
+        if BLOCKS.contains(&name) {
+            push_break(&mut text, &mut emphasis);
+        } else if name == "td" || name == "th" {
+            push_text(&mut text, &mut emphasis, " ", false);
+        }
+
+        if tag.closing {
+            if let Some(index) = stack.iter().rposition(|open| open == name) {
+                stack.truncate(index);
+                if emphasis.as_ref().is_some_and(|(depth, _)| *depth >= index) {
+                    let (_, snippet) = emphasis.take().unwrap();
+                    let snippet = collapse_whitespace(&snippet);
+                    if !snippet.is_empty() && snippet.chars().count() <= MAX_EMPHASIZED_CHARS {
+                        emphasized.push(snippet);
+                    }
+                }
+            }
+            continue;
+        }
+
+        if SKIPPED.contains(&name) && !tag.self_closing {
+            rest = skip_element(rest, name);
+            continue;
+        }
+        if VOID.contains(&name) || tag.self_closing {
+            continue;
+        }
+        if emphasis.is_none() && is_emphasized(name, &tag.attributes) {
+            emphasis = Some((stack.len(), String::new()));
+        }
+        stack.push(tag.name);
+    }
+
+    HtmlText {
+        text: text
+            .lines()
+            .map(collapse_whitespace)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        emphasized,
+    }
+}
+
+fn in_pre(stack: &[String]) -> bool {
+    stack.iter().any(|name| name == "pre")
+}
+
+fn push_text(text: &mut String, emphasis: &mut Option<(usize, String)>, raw: &str, pre: bool) {
+    if raw.is_empty() {
+        return;
+    }
+    let decoded = decode_entities(raw);
+    // 源码中的换行不是文字的一部分，只有 <pre> 中保留
+    let decoded = if pre {
+        decoded
+    } else {
+        decoded.replace(['\r', '\n', '\t'], " ")
+    };
+    text.push_str(&decoded);
+    if let Some((_, snippet)) = emphasis {
+        snippet.push_str(&decoded);
+    }
+}
+
+fn push_break(text: &mut String, emphasis: &mut Option<(usize, String)>) {
+    text.push('\n');
+    if let Some((_, snippet)) = emphasis {
+        snippet.push(' ');
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+struct Tag {
+    /// 小写的元素名称，声明和处理指令为空
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    /// 小写的属性名称和原样的属性值
+    attributes: Vec<(String, String)>,
+    /// 标签在源码中的字节长度
+    length: usize,
+}
+
+// 解析 `rest` 开头的标签，开头不是合法标签时返回 None
+fn parse_tag(rest: &str) -> Option<Tag> {
+    let after = &rest[1..];
+    let first = after.chars().next()?;
+    if first == '!' || first == '?' {
+        let end = after.find('>')?;
+        return Some(Tag {
+            name: String::new(),
+            closing: false,
+            self_closing: true,
+            attributes: Vec::new(),
+            length: end + 2,
+        });
+    }
+    let closing = first == '/';
+    let body = if closing { &after[1..] } else { after };
+    if !body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    // 找到不在引号中的 '>'
+    let mut quote = None;
+    let end = body.char_indices().find_map(|(i, c)| {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+        None
+    })?;
+    let inner = &body[..end];
+    let name_end = inner
+        .find(|c: char| c.is_whitespace() || c == '/')
+        .unwrap_or(inner.len());
+
+    Some(Tag {
+        name: inner[..name_end].to_ascii_lowercase(),
+        closing,
+        self_closing: inner.trim_end().ends_with('/'),
+        attributes: parse_attributes(&inner[name_end..]),
+        length: 1 + usize::from(closing) + end + 1,
+    })
+}
+
+fn parse_attributes(mut rest: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            return attributes;
+        }
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(q @ ('"' | '\'')) => match after[1..].find(q) {
+                    Some(end) => (&after[1..end + 1], &after[end + 2..]),
+                    None => (&after[1..], ""),
+                },
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        attributes.push((name, value));
+    }
+}
+
+// 跳过 <script>、<style> 等元素直到结束标签，没有结束标签时丢弃剩余内容
+fn skip_element<'a>(rest: &'a str, name: &str) -> &'a str {
+    let closing = format!("</{}", name);
+    let lower = rest.to_ascii_lowercase();
+    match lower.find(&closing) {
+        Some(start) => {
+            let after = &rest[start..];
+            after.find('>').map_or("", |end| &after[end + 1..])
+        }
+        None => "",
+    }
+}
+
+// 加粗、大号字体（不小于 20px）或等宽字体的元素
+fn is_emphasized(name: &str, attributes: &[(String, String)]) -> bool {
+    if EMPHASIS.contains(&name) {
+        return true;
+    }
+    let attribute = |key: &str| {
+        attributes
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.to_ascii_lowercase())
+    };
+
+    // <font size="5"> 及以上
+    if name == "font" {
+        let large = attribute("size")
+            .and_then(|size| size.trim().trim_start_matches('+').parse::<u32>().ok())
+            .is_some_and(|size| size >= 5);
+        let monospace = attribute("face")
+            .is_some_and(|face| MONOSPACE_FONTS.iter().any(|font| face.contains(font)));
+        if large || monospace {
+            return true;
+        }
+    }
+
+    let Some(style) = attribute("style") else {
+        return false;
+    };
+    style.split(';').any(|declaration| {
+        let Some((property, value)) = declaration.split_once(':') else {
+            return false;
+        };
+        let value = value.trim().trim_end_matches("!important").trim();
+        match property.trim() {
+            "font-weight" => {
+                matches!(value, "bold" | "bolder") || value.parse::<u32>().is_ok_and(|w| w >= 600)
+            }
+            "font-family" => MONOSPACE_FONTS.iter().any(|font| value.contains(font)),
+            "font-size" => is_large_font(value),
+            "font" => {
+                value.contains("bold")
+                    || MONOSPACE_FONTS.iter().any(|font| value.contains(font))
+                    || value.split_whitespace().any(is_large_font)
+            }
+            _ => false,
+        }
+    })
+}
+
+fn is_large_font(value: &str) -> bool {
+    if matches!(value, "x-large" | "xx-large" | "xxx-large") {
+        return true;
+    }
+    // 换算成 px 比较
+    let units = [("px", 1.0), ("pt", 4.0 / 3.0), ("rem", 16.0), ("em", 16.0)];
+    units.iter().any(|(unit, scale)| {
+        value
+            .strip_suffix(unit)
+            .and_then(|number| number.trim().parse::<f32>().ok())
+            .is_some_and(|size| size * scale >= 20.0)
+    })
+}
+
+/// 解码 HTML 实体，无法识别的实体原样保留
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end + 1])?, end + 2)));
+        match decoded {
+            Some((decoded, length)) => {
+                out.push_str(&decoded);
+                rest = &rest[length..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<String> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code).map(String::from);
+    }
+    let decoded = match entity {
+        "amp" => "&",
+        "lt" => "<",
+        "gt" => ">",
+        "quot" => "\"",
+        "apos" => "'",
+        "nbsp" | "ensp" | "emsp" | "thinsp" => " ",
+        "zwnj" | "zwj" | "shy" => "",
+        "copy" => "©",
+        "reg" => "®",
+        "trade" => "™",
+        "middot" => "·",
+        "bull" => "•",
+        "hellip" => "…",
+        "ndash" => "–",
+        "mdash" => "—",
+        "lsquo" => "‘",
+        "rsquo" => "’",
+        "ldquo" => "“",
+        "rdquo" => "”",
+        "laquo" => "«",
+        "raquo" => "»",
+        "yen" => "¥",
+        "euro" => "€",
+        "pound" => "£",
+        _ => return None,
+    };
+    Some(decoded.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Verify</title><style>p { color: red; }</style></head>
+<body>
+  <script type="text/javascript">var code = "000000";</script>
+  <!-- tracking 111111 -->
+  <div>Hi&nbsp;there,</div><p>Your sign-in code is
+    <span style="font-size: 24px; letter-spacing: 4px">482&#32;913</span></p>
+  <table><tr><td>Order</td><td>#771234</td></tr></table>
+  <p>Use it in <b>10 minutes</b> &amp; don&#x27;t share it. 1 &lt; 2</p>
+  <pre>  A1B2-C3D4  </pre><br/>
+  <p style="font-family: 'Courier New', monospace">A very long monospace paragraph that is not a code at all</p>
+</body></html>"#;
+        let converted = to_text(html);
+        assert_eq!(
+            converted.text,
+            "Hi there,\nYour sign-in code is 482 913\nOrder #771234\nUse it in 10 minutes & don't share it. 1 < 2\nA1B2-C3D4\nA very long monospace paragraph that is not a code at all"
+        );
+        assert_eq!(
+            converted.emphasized,
+            vec!["482 913", "10 minutes", "A1B2-C3D4"]
+        );
+
+        // 不规范的输入
+        let converted = to_text("a < b <b>5 &bogus; 6</b");
+        assert_eq!(converted.text, "a < b 5 &bogus; 6</b");
+        assert!(converted.emphasized.is_empty());
+        assert_eq!(to_text("<p>unclosed <script>x").text, "unclosed");
+        assert_eq!(
+            to_text("<font size=\"6\">1234</font>").emphasized,
+            vec!["1234"]
+        );
+    }
+
+    proptest! {
+        // HTML 正文来自外部，任意内容都不能让监听线程 panic
+        #[test]
+        fn prop_html_conversion_never_panics(html in "(<[a-z/!?]{0,3}[^<>]{0,10}>?|&#?[a-z0-9]{0,5};?|[^<&]{0,8})*") {
+            to_text(&html);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! 邮件 App 数据目录的检测，以及根据配置决定监听其中的哪些邮箱
+//!
+//! 邮件 App 的数据目录结构为 `<账户 UUID>/<邮箱>.mbox/<子邮箱>.mbox/.../Messages/<编号>.emlx`，
+//! 邮箱名称由各级 `.mbox` 目录名去掉后缀、用 `/` 连接而成，如 `[Gmail]/All Mail`。
+
+use crate::config::MailboxOptions;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 找不到 V* 目录时使用的数据目录，对应 macOS 12 及以后的邮件 App
+const DEFAULT_DATA_DIR: &str = "V10";
+
+// 开启 `all` 时跳过的邮箱，与邮箱名称的最后一级比较，不区分大小写
+const SKIPPED_MAILBOXES: &[&str] = &[
+    "sent",
+    "sent messages",
+    "sent items",
+    "sent mail",
+    "drafts",
+    "trash",
+    "deleted messages",
+    "deleted items",
+    "bin",
+    "outbox",
+    "已发送",
+    "已发送邮件",
+    "草稿",
+    "草稿箱",
+    "已删除",
+    "已删除邮件",
+    "废纸篓",
+];
+
+/// `dir` 下版本号最大的 V* 目录，如同时存在 V9 和 V10 时返回 V10
+pub fn find_data_dir(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let version: u32 = name.strip_prefix('V')?.parse().ok()?;
+            Some((version, entry.path()))
+        })
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, path)| path)
+}
+
+/// 确定邮件 App 的数据目录。
+///
+/// 配置了 `mail_root` 时以它为准，其中有 V* 目录时使用最新的一个；
+/// 否则在 `library_mail`（即 `~/Library/Mail`）中查找，找不到时退回 V10。
+pub fn resolve_mail_root(configured: Option<&Path>, library_mail: &Path) -> PathBuf {
+    if let Some(configured) = configured {
+        let configured = expand_home(configured);
+        if !configured.is_dir() {
+            log::warn!("Configured mail_root does not exist: {:?}", configured);
+        }
+        return find_data_dir(&configured).unwrap_or(configured);
+    }
+    find_data_dir(library_mail).unwrap_or_else(|| {
+        let fallback = library_mail.join(DEFAULT_DATA_DIR);
+        log::warn!(
+            "No Mail data directory found in {:?}, falling back to {:?}",
+            library_mail,
+            fallback
+        );
+        fallback
+    })
+}
+
+// 配置文件中的 "~/Library/Mail" 展开为用户目录下的路径
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// 一封邮件所在的账户目录和邮箱
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailboxLocation {
+    /// 账户目录名，通常是 UUID
+    pub account: String,
+    pub mailbox: String,
+}
+
+/// 根据邮件文件相对数据目录的位置确定账户和邮箱，不在任何邮箱中时返回 None
+pub fn locate(root: &Path, path: &Path) -> Option<MailboxLocation> {
+    let mut components = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy());
+    let account = components.next()?.to_string();
+    let mailbox: Vec<String> = components
+        .map_while(|component| component.strip_suffix(".mbox").map(str::to_string))
+        .collect();
+    if mailbox.is_empty() {
+        return None;
+    }
+    Some(MailboxLocation {
+        account,
+        mailbox: mailbox.join("/"),
+    })
+}
+
+/// 账户目录对应的名称，未配置时为目录名本身
+pub fn account_name<'a>(options: &'a MailboxOptions, account: &'a str) -> &'a str {
+    options
+        .account_names
+        .get(account)
+        .map_or(account, String::as_str)
+}
+
+/// 数据目录下的账户目录名，用于在日志中列出，方便填写 `account_names`
+pub fn list_accounts(root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut accounts: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        // MailData 等目录中没有邮箱
+        .filter(|name| name.contains('-'))
+        .collect();
+    accounts.sort();
+    accounts
+}
+
+/// 是否监听该邮箱：`exclude` 优先；`include` 中列出的邮箱总是监听；
+/// 开启 `all` 时其他邮箱除已发送、草稿和废纸篓外也都监听。账户的设置覆盖全局设置
+pub fn is_watched(options: &MailboxOptions, location: &MailboxLocation) -> bool {
+    let name = account_name(options, &location.account);
+    let account = options.accounts.iter().find(|a| {
+        a.account.eq_ignore_ascii_case(&location.account) || a.account.eq_ignore_ascii_case(name)
+    });
+    let include = account
+        .and_then(|a| a.include.as_ref())
+        .unwrap_or(&options.include);
+    let exclude = account
+        .and_then(|a| a.exclude.as_ref())
+        .unwrap_or(&options.exclude);
+    let all = account.and_then(|a| a.all).unwrap_or(options.all);
+
+    let mailbox = location.mailbox.as_str();
+    if exclude.iter().any(|pattern| glob_match(pattern, mailbox)) {
+        return false;
+    }
+    if include.iter().any(|pattern| glob_match(pattern, mailbox)) {
+        return true;
+    }
+    all && !is_skipped(mailbox)
+}
+
+fn is_skipped(mailbox: &str) -> bool {
+    let last = mailbox.rsplit('/').next().unwrap_or(mailbox).to_lowercase();
+    SKIPPED_MAILBOXES.contains(&last.as_str())
+}
+
+/// 不区分大小写的 glob 匹配：`*` 匹配不含 `/` 的任意文字，`**` 可以跨越 `/`，`?` 匹配单个字符
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // matches[j] 表示模式的当前前缀能否匹配文字的前 j 个字符
+    let mut matches = vec![false; text.len() + 1];
+    matches[0] = true;
+    let mut i = 0;
+    while i < pattern.len() {
+        let mut next = vec![false; text.len() + 1];
+        match pattern[i] {
+            '*' => {
+                let crosses = pattern.get(i + 1) == Some(&'*');
+                if crosses {
+                    i += 1;
+                }
+                for j in 0..=text.len() {
+                    next[j] =
+                        matches[j] || (j > 0 && next[j - 1] && (crosses || text[j - 1] != '/'));
+                }
+            }
+            c => {
+                for j in 1..=text.len() {
+                    next[j] =
+                        matches[j - 1] && ((c == '?' && text[j - 1] != '/') || c == text[j - 1]);
+                }
+            }
+        }
+        matches = next;
+        i += 1;
+    }
+    matches[text.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AccountMailboxes;
+    use std::path::PathBuf;
+
+    const ACCOUNT: &str = "3F2504E0-4F89-11D3-9A0C-0305E82C3301";
+
+    fn location(mailbox: &str) -> MailboxLocation {
+        MailboxLocation {
+            account: ACCOUNT.to_string(),
+            mailbox: mailbox.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_detect_newest_data_dir() {
+        let library_mail =
+            std::env::temp_dir().join(format!("messauto-mail-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&library_mail);
+
+        // 还没有任何 V* 目录时退回 V10
+        fs::create_dir_all(library_mail.join("MailData")).unwrap();
+        assert_eq!(
+            resolve_mail_root(None, &library_mail),
+            library_mail.join("V10")
+        );
+
+        for dir in ["V2", "V9", "V11", "Vx", "V12.bak"] {
+            fs::create_dir_all(library_mail.join(dir)).unwrap();
+        }
+        fs::write(library_mail.join("V99"), "").unwrap();
+        let newest = library_mail.join("V11");
+        assert_eq!(find_data_dir(&library_mail), Some(newest.clone()));
+        assert_eq!(resolve_mail_root(None, &library_mail), newest);
+
+        // 配置的目录优先，可以是 V* 目录本身或它的上级目录
+        let v9 = library_mail.join("V9");
+        assert_eq!(resolve_mail_root(Some(&v9), Path::new("/nonexistent")), v9);
+        assert_eq!(
+            resolve_mail_root(Some(&library_mail), Path::new("/nonexistent")),
+            newest
+        );
+
+        // 检测到的目录中的邮件可以定位到邮箱
+        let message = newest
+            .join(ACCOUNT)
+            .join("INBOX.mbox/1A2B/Data/Messages/1.emlx");
+        assert_eq!(locate(&newest, &message), Some(location("INBOX")));
+
+        fs::remove_dir_all(&library_mail).unwrap();
+    }
+
+    #[test]
+    fn test_locate_mailbox_from_path() {
+        let root = PathBuf::from("/Users/me/Library/Mail/V10");
+        let path = root
+            .join(ACCOUNT)
+            .join("[Gmail].mbox/All Mail.mbox/1A2B/Data/3/Messages/12345.emlx");
+        assert_eq!(locate(&root, &path), Some(location("[Gmail]/All Mail")));
+        assert!(locate(&root, &root.join("MailData/Envelope Index")).is_none());
+        assert!(locate(Path::new("/elsewhere"), &path).is_none());
+    }
+
+    #[test]
+    fn test_mailbox_selection() {
+        // 默认只监听收件箱，与之前的行为一致
+        let mut options = MailboxOptions::default();
+        assert!(is_watched(&options, &location("INBOX")));
+        assert!(is_watched(&options, &location("Inbox/Receipts")));
+        assert!(!is_watched(&options, &location("[Gmail]/All Mail")));
+
+        // 监听全部邮箱时跳过已发送、草稿和废纸篓，exclude 优先
+        options.all = true;
+        options.exclude = vec!["Archive/**".to_string()];
+        assert!(is_watched(&options, &location("[Gmail]/All Mail")));
+        assert!(is_watched(&options, &location("Junk")));
+        assert!(!is_watched(&options, &location("[Gmail]/Sent Mail")));
+        assert!(!is_watched(&options, &location("Deleted Messages")));
+        assert!(!is_watched(&options, &location("Archive/2024/March")));
+
+        // 按账户名称覆盖全局设置
+        options
+            .account_names
+            .insert(ACCOUNT.to_string(), "Work".to_string());
+        options.accounts = vec![AccountMailboxes {
+            account: "work".to_string(),
+            all: Some(false),
+            include: Some(vec!["INBOX".to_string(), "Other".to_string()]),
+            exclude: None,
+        }];
+        assert_eq!(account_name(&options, ACCOUNT), "Work");
+        assert!(is_watched(&options, &location("Other")));
+        assert!(!is_watched(&options, &location("Junk")));
+        let personal = MailboxLocation {
+            account: "OTHER-UUID".to_string(),
+            mailbox: "Junk".to_string(),
+        };
+        assert!(is_watched(&options, &personal));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("inbox", "INBOX"));
+        assert!(glob_match("[Gmail]/*", "[Gmail]/Spam"));
+        assert!(!glob_match("[Gmail]/*", "[Gmail]/Labels/Bank"));
+        assert!(glob_match("[Gmail]/**", "[Gmail]/Labels/Bank"));
+        assert!(glob_match("Recei?ts", "Receipts"));
+        assert!(!glob_match("INBOX", "INBOX/Sub"));
+    }
+}
@@ -6,25 +6,29 @@ use std::sync::Mutex;
 
 use super::watcher::FileProcessor;
 use crate::clipboard;
-use crate::config::Config;
+use crate::history;
 use crate::ipc;
-use crate::parser;
+use crate::parser::corrections::PendingCorrection;
+use crate::parser::{Extraction, SharedExtractor};
+use crate::tray;
 
 // 跟踪最后处理的消息ID
 static LAST_PROCESSED_ROWID: Mutex<i64> = Mutex::new(0);
 
 #[derive(Clone)]
-pub struct MessageProcessor;
+pub struct MessageProcessor {
+    extractor: SharedExtractor,
+}
 
 impl MessageProcessor {
-    pub fn new() -> Self {
+    pub fn new(extractor: SharedExtractor) -> Self {
         if let Ok(rowid) = Self::get_latest_message_rowid() {
             let mut last_processed = LAST_PROCESSED_ROWID.lock().unwrap();
             *last_processed = rowid;
             info!("Initialized last processed ROWID to {}", rowid);
         }
 
-        Self {}
+        Self { extractor }
     }
 
     // 获取数据库中最新的消息ROWID
@@ -115,17 +119,77 @@ impl FileProcessor for MessageProcessor {
             let messages = parse_sqlite_output(&output_str);
             debug!("Parsed {} messages", messages.len());
 
+            let extractor = self.extractor.current();
+            let config = extractor.config();
+
             for (i, message) in messages.iter().enumerate() {
-                debug!("Processing message {}: {}", i, message);
-                if let Some(code) = parser::extract_verification_code(message) {
-                    info!("Found verification code in message: {}", code);
+                debug!("Processing message {}: {}", i, message.text);
+                // 整条链只运行一次，没有验证码时才使用检测到的登录链接
+                let extraction = extractor.run(&message.text, message.sender.as_deref());
+                if let Some(Extraction::Code(result)) = extraction {
+                    let code = result.code.clone();
+                    info!(
+                        "Found verification code in message: {} (keyword: {}, distance: {}, confidence: {:.2})",
+                        code, result.keyword, result.distance, result.confidence
+                    );
+                    if let Some(rule) = &result.rule {
+                        info!("Matched sender rule: {}", rule);
+                    }
+                    if let Some(service) = &result.service {
+                        info!("Identified service: {}", service);
+                    }
+                    for candidate in &result.rejected {
+                        debug!(
+                            "Rejected candidate {} at {:?}: {}",
+                            candidate.code, candidate.span, candidate.reason
+                        );
+                    }
+                    for alternate in &result.alternates {
+                        info!(
+                            "Alternate code: {} ({:?}, confidence: {:.2})",
+                            alternate.code, alternate.role, alternate.confidence
+                        );
+                    }
 
-                    let config = Config::load().unwrap_or_default();
+                    // 取件码等非验证码不自动输入，只记入历史
+                    if !result.category.is_otp() {
+                        match history::record_pickup_code(
+                            &code,
+                            result.service.as_deref(),
+                            result.validity,
+                        ) {
+                            Ok(active) => info!(
+                                "Recorded {:?} code {} in history ({} active)",
+                                result.category, code, active
+                            ),
+                            Err(e) => error!("Failed to record pickup code: {}", e),
+                        }
+                        continue;
+                    }
 
                     // 如果悬浮窗启用，只显示悬浮窗，不自动输入
                     if config.floating_window {
-                        match ipc::spawn_floating_window(&code, "iMessage") {
-                            Ok(_) => debug!("Floating window spawned successfully"),
+                        // 纠正需要的信息随悬浮窗进程的标准输入传入，验证码不对时可以在悬浮窗中改选
+                        let correction = PendingCorrection::from_result(
+                            &message.text,
+                            message.sender.as_deref(),
+                            &result,
+                        );
+                        match ipc::spawn_floating_window(
+                            &code,
+                            result.service.as_deref().unwrap_or("iMessage"),
+                            &result.alternates,
+                            result.validity,
+                            Some(&correction),
+                        ) {
+                            Ok(child) => {
+                                debug!("Floating window spawned successfully");
+                                ipc::learn_from_floating_window(
+                                    child,
+                                    correction,
+                                    self.extractor.clone(),
+                                );
+                            }
                             Err(e) => error!("Failed to spawn floating window: {}", e),
                         }
                     } else {
@@ -182,6 +246,18 @@ impl FileProcessor for MessageProcessor {
                             }
                         }
                     }
+                } else if let Some(Extraction::SignInLink(link)) = extraction {
+                    info!(
+                        "Found sign-in link in message near {:?}: {}",
+                        link.keyword, link.url
+                    );
+                    tray::set_sign_in_link(&link.url);
+                    if config.floating_window {
+                        match ipc::spawn_sign_in_link_window(&link.url, "iMessage") {
+                            Ok(_) => debug!("Sign-in link window spawned successfully"),
+                            Err(e) => error!("Failed to spawn sign-in link window: {}", e),
+                        }
+                    }
                 } else {
                     debug!("No verification code found in message");
                 }
@@ -208,7 +284,14 @@ impl FileProcessor for MessageProcessor {
     }
 }
 
-fn parse_sqlite_output(output: &str) -> Vec<String> {
+// 从 chat.db 中读出的一条消息
+struct IncomingMessage {
+    text: String,
+    // 发件人的 handle（h.id），可能是手机号、邮箱或短号
+    sender: Option<String>,
+}
+
+fn parse_sqlite_output(output: &str) -> Vec<IncomingMessage> {
     let mut result = Vec::new();
 
     for line in output.lines() {
@@ -216,9 +299,25 @@ fn parse_sqlite_output(output: &str) -> Vec<String> {
             let parts: Vec<&str> = line.split('|').collect();
             if parts.len() >= 2 {
                 let id = parts[0].trim();
-                let text = parts[1].trim();
-                debug!("New message found with ID {}: {}", id, text);
-                result.push(text.to_string());
+                // 查询的列依次为 ROWID|text|phone_number|date，正文中可能包含 '|'
+                let (text, sender) = if parts.len() >= 4 {
+                    let sender = parts[parts.len() - 2].trim();
+                    (
+                        parts[1..parts.len() - 2].join("|"),
+                        (!sender.is_empty()).then(|| sender.to_string()),
+                    )
+                } else {
+                    (parts[1].to_string(), None)
+                };
+                let text = text.trim();
+                debug!(
+                    "New message found with ID {} from {:?}: {}",
+                    id, sender, text
+                );
+                result.push(IncomingMessage {
+                    text: text.to_string(),
+                    sender,
+                });
             }
         }
     }
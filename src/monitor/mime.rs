@@ -0,0 +1,536 @@
+use encoding_rs::{Encoding, GB18030, ISO_2022_JP};
+use std::fmt;
+
+// 嵌套 multipart 的最大层数，防止构造的邮件导致栈溢出
+const MAX_DEPTH: usize = 16;
+
+/// MIME 邮件中的一个部分，正文已按 Content-Transfer-Encoding 解码为原始字节，
+/// 字符集解码推迟到 [`MimePart::text`]。
+///
+/// 直接在字节上解析，不要求邮件整体是 UTF-8，GBK、Big5、Shift_JIS 等 8 位编码的邮件也能解析。
+#[derive(Debug, Default)]
+pub struct MimePart {
+    /// 头部字段，值已解码 RFC 2047 编码字
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    pub children: Vec<MimePart>,
+}
+
+pub fn parse(raw: &[u8]) -> MimePart {
+    parse_part(raw, 0)
+}
+
+fn parse_part(raw: &[u8], depth: usize) -> MimePart {
+    let (header_bytes, body) = split_header_body(raw);
+    let raw_headers = parse_headers(header_bytes);
+
+    // 头部中不规范的 8 位字符按本部分声明的字符集解码
+    let charset = raw_headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+        .and_then(|(_, value)| param(&String::from_utf8_lossy(value), "charset"));
+    let headers = raw_headers
+        .into_iter()
+        .map(|(name, value)| {
+            let value = decode_text(&value, charset.as_deref());
+            (name, decode_encoded_words(value.trim()))
+        })
+        .collect();
+
+    let mut part = MimePart {
+        headers,
+        ..MimePart::default()
+    };
+
+    let boundary = part
+        .header("Content-Type")
+        .and_then(|value| param(value, "boundary"));
+    match boundary {
+        Some(boundary) if part.content_type().starts_with("multipart/") && depth < MAX_DEPTH => {
+            part.children = split_multipart(body, &boundary)
+                .into_iter()
+                .map(|child| parse_part(child, depth + 1))
+                .collect();
+        }
+        _ => {
+            let encoding = part
+                .header("Content-Transfer-Encoding")
+                .map(|value| value.trim().to_ascii_lowercase());
+            part.body = match encoding.as_deref() {
+                Some("base64") => decode_base64(body),
+                Some("quoted-printable") => decode_quoted_printable(body),
+                _ => body.to_vec(),
+            };
+        }
+    }
+    part
+}
+
+impl MimePart {
+    /// 第一个名称匹配（不区分大小写）的头部字段
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 小写的媒体类型，未声明时为 "text/plain"
+    pub fn content_type(&self) -> String {
+        self.header("Content-Type")
+            .and_then(|value| value.split(';').next())
+            .map(|media_type| media_type.trim().to_ascii_lowercase())
+            .filter(|media_type| !media_type.is_empty())
+            .unwrap_or_else(|| "text/plain".to_string())
+    }
+
+    pub fn charset(&self) -> Option<String> {
+        self.header("Content-Type")
+            .and_then(|value| param(value, "charset"))
+    }
+
+    pub fn is_attachment(&self) -> bool {
+        self.header("Content-Disposition")
+            .is_some_and(|value| value.trim().to_ascii_lowercase().starts_with("attachment"))
+    }
+
+    /// 按声明的字符集解码后的正文
+    pub fn text(&self) -> String {
+        decode_text(&self.body, self.charset().as_deref())
+    }
+
+    /// 深度优先查找第一个非空的 text/plain 部分，附件除外
+    pub fn plain_text(&self) -> Option<String> {
+        self.find_text("text/plain")
+    }
+
+    /// 深度优先查找第一个非空的 text/html 部分，附件除外
+    pub fn html(&self) -> Option<String> {
+        self.find_text("text/html")
+    }
+
+    fn find_text(&self, media_type: &str) -> Option<String> {
+        if !self.children.is_empty() {
+            return self
+                .children
+                .iter()
+                .find_map(|child| child.find_text(media_type));
+        }
+        if self.is_attachment() || self.content_type() != media_type {
+            return None;
+        }
+        let text = self.text();
+        let text = text.trim();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text.to_string())
+        }
+    }
+}
+
+/// 邮件地址及显示名称，如 `"Slack" <no-reply@slack.com>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mailbox {
+    pub name: Option<String>,
+    pub address: String,
+}
+
+impl Mailbox {
+    /// 解析 From 等头部的值，支持 `Name <addr>`、`addr (Name)` 和单独的地址
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (name, address) = match (value.rfind('<'), value.rfind('>')) {
+            (Some(open), Some(close)) if open < close => {
+                (&value[..open], value[open + 1..close].trim())
+            }
+            _ => match value.split_once('(') {
+                Some((address, comment)) => (comment.trim_end_matches(')'), address.trim()),
+                None => ("", value),
+            },
+        };
+        if address.is_empty() || address.contains(char::is_whitespace) {
+            return None;
+        }
+
+        let name = name.trim().trim_matches('"').trim();
+        Some(Self {
+            name: (!name.is_empty()).then(|| name.to_string()),
+            address: address.to_string(),
+        })
+    }
+
+    /// 显示名称，没有时为地址
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.address)
+    }
+}
+
+impl fmt::Display for Mailbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} <{}>", name, self.address),
+            None => write!(f, "{}", self.address),
+        }
+    }
+}
+
+// 在第一个空行处分开头部和正文，兼容 CRLF 和 LF
+fn split_header_body(raw: &[u8]) -> (&[u8], &[u8]) {
+    let mut start = 0;
+    while start < raw.len() {
+        let end = raw[start..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(raw.len(), |i| start + i);
+        let line = &raw[start..end];
+        if line.is_empty() || line == b"\r" {
+            return (&raw[..start], &raw[(end + 1).min(raw.len())..]);
+        }
+        start = end + 1;
+    }
+    (raw, &[])
+}
+
+// 解析头部字段并展开折行，值保留原始字节
+fn parse_headers(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let mut headers: Vec<(String, Vec<u8>)> = Vec::new();
+    for line in bytes.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.first().is_some_and(|b| *b == b' ' || *b == b'\t') {
+            if let Some((_, value)) = headers.last_mut() {
+                value.extend_from_slice(line);
+            }
+            continue;
+        }
+        if let Some(colon) = line.iter().position(|b| *b == b':') {
+            let name = String::from_utf8_lossy(&line[..colon]).trim().to_string();
+            headers.push((name, line[colon + 1..].to_vec()));
+        }
+    }
+    headers
+}
+
+/// 取出头部值中的参数，如 `text/plain; charset="gbk"` 中的 charset
+fn param(value: &str, name: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => segments.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    segments.push(current);
+
+    segments.into_iter().skip(1).find_map(|segment| {
+        let (key, value) = segment.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().to_string())
+    })
+}
+
+// 按边界拆分 multipart 正文，第一个边界之前和结束边界之后的内容被丢弃
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut part_start: Option<usize> = None;
+
+    let mut start = 0;
+    while start < body.len() {
+        let end = body[start..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(body.len(), |i| start + i);
+        let line = body[start..end].trim_ascii_end();
+        let rest = line
+            .strip_prefix(delimiter.as_bytes())
+            .filter(|rest| rest.is_empty() || *rest == b"--");
+        if let Some(rest) = rest {
+            if let Some(part_start) = part_start {
+                parts.push(&body[part_start..start]);
+            }
+            if rest == b"--" {
+                return parts;
+            }
+            part_start = Some((end + 1).min(body.len()));
+        }
+        start = end + 1;
+    }
+    // 缺少结束边界时保留最后一部分
+    if let Some(part_start) = part_start {
+        parts.push(&body[part_start..]);
+    }
+    parts
+}
+
+fn decode_base64(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for b in bytes {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            // 换行等其他字符直接跳过
+            _ => continue,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    out
+}
+
+fn decode_quoted_printable(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'=' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        // "=\r\n" 和 "=\n" 是软换行
+        let rest = &bytes[i + 1..];
+        if rest.starts_with(b"\r\n") {
+            i += 3;
+        } else if rest.starts_with(b"\n") {
+            i += 2;
+        } else if let Some(byte) = rest.get(..2).and_then(hex_byte) {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(b'=');
+            i += 1;
+        }
+    }
+    out
+}
+
+fn hex_byte(pair: &[u8]) -> Option<u8> {
+    let digit = |b: u8| (b as char).to_digit(16);
+    Some((digit(pair[0])? * 16 + digit(pair[1])?) as u8)
+}
+
+fn encoding_for(label: &str) -> Option<&'static Encoding> {
+    // WHATWG 标签中没有的 Windows 代码页名称
+    let label = match label.trim().to_ascii_lowercase().as_str() {
+        "cp936" | "ms936" | "windows-936" => "gbk".to_string(),
+        "cp932" | "ms932" => "shift_jis".to_string(),
+        "cp950" | "ms950" => "big5".to_string(),
+        other => other.to_string(),
+    };
+    Encoding::for_label(label.as_bytes())
+}
+
+/// 按字符集解码文本。未声明字符集或声明为 us-ascii 时先按 UTF-8 解码，
+/// 不是合法的 UTF-8 时按 GB18030（GBK 的超集）解码，国内服务的邮件常省略字符集
+fn decode_text(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .filter(|label| !label.trim().eq_ignore_ascii_case("us-ascii"))
+        .and_then(encoding_for);
+    match encoding {
+        Some(encoding) => encoding.decode(bytes).0.into_owned(),
+        None => match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => GB18030.decode(bytes).0.into_owned(),
+        },
+    }
+}
+
+/// 解码头部值中的 RFC 2047 编码字，如 `=?GBK?B?0enWpMLr?=`。
+///
+/// 相邻编码字之间的空白被丢弃；字符集相同的相邻编码字先拼接字节再解码，
+/// 多字节字符被拆到两个编码字中时也能正确解码。ISO-2022-JP 是有状态的编码，
+/// 每个编码字本身完整，拼接后两个转义序列相连反而会被当作错误，因此不拼接。
+pub fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut pending: Option<(String, Vec<u8>)> = None;
+    let mut rest = value;
+
+    let flush = |pending: &mut Option<(String, Vec<u8>)>, out: &mut String| {
+        if let Some((charset, bytes)) = pending.take() {
+            out.push_str(&decode_text(&bytes, Some(&charset)));
+        }
+    };
+
+    while let Some((start, end, charset, bytes)) = find_encoded_word(rest) {
+        let between = &rest[..start];
+        let adjacent = pending.is_some() && between.trim().is_empty();
+        match &mut pending {
+            Some((pending_charset, pending_bytes))
+                if adjacent
+                    && pending_charset.eq_ignore_ascii_case(&charset)
+                    && encoding_for(&charset) != Some(ISO_2022_JP) =>
+            {
+                pending_bytes.extend(bytes);
+            }
+            _ => {
+                flush(&mut pending, &mut out);
+                if !adjacent {
+                    out.push_str(between);
+                }
+                pending = Some((charset, bytes));
+            }
+        }
+        rest = &rest[end..];
+    }
+    flush(&mut pending, &mut out);
+    out.push_str(rest);
+    out
+}
+
+// 返回编码字的起止位置、字符集和解码后的字节
+fn find_encoded_word(text: &str) -> Option<(usize, usize, String, Vec<u8>)> {
+    let mut search = 0;
+    while let Some(offset) = text[search..].find("=?") {
+        let start = search + offset;
+        let inner = &text[start + 2..];
+        let parsed = (|| {
+            let (charset, inner) = inner.split_once('?')?;
+            let (encoding, inner) = inner.split_once('?')?;
+            let (encoded, _) = inner.split_once("?=")?;
+            if charset.is_empty() || encoded.contains(char::is_whitespace) {
+                return None;
+            }
+            let bytes = match encoding {
+                "B" | "b" => decode_base64(encoded.as_bytes()),
+                "Q" | "q" => decode_quoted_printable(encoded.replace('_', " ").as_bytes()),
+                _ => return None,
+            };
+            let length = charset.len() + encoding.len() + encoded.len() + 6;
+            // RFC 2231 允许在字符集后附加语言，如 "UTF-8*zh"
+            let charset = charset.split('*').next().unwrap_or(charset).to_string();
+            Some((start + length, charset, bytes))
+        })();
+        if let Some((end, charset, bytes)) = parsed {
+            return Some((start, end, charset, bytes));
+        }
+        search = start + 2;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::path::Path;
+
+    // 去掉 .emlx 首行的字节数和末尾的 plist，只保留 RFC 822 邮件
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/emlx")
+            .join(name);
+        let bytes = std::fs::read(&path).unwrap();
+        let newline = bytes.iter().position(|b| *b == b'\n').unwrap();
+        let length: usize = std::str::from_utf8(&bytes[..newline])
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        bytes[newline + 1..newline + 1 + length].to_vec()
+    }
+
+    #[test]
+    fn test_fixture_emails_in_legacy_charsets() {
+        let cases = [
+            ("gbk.emlx", "【阿里云】验证码", "阿里云", "验证码：482913"),
+            ("big5.emlx", "驗證碼通知", "蝦皮購物", "驗證碼為 739201"),
+            (
+                "shift_jis.emlx",
+                "認証コードのお知らせ",
+                "楽天",
+                "認証コード：294813",
+            ),
+            (
+                "iso-2022-jp.emlx",
+                "ワンタイムパスワード",
+                "ヤフー",
+                "パスワード：583920",
+            ),
+        ];
+        for (name, subject, from, body) in cases {
+            let message = parse(&fixture(name));
+            assert_eq!(message.header("Subject"), Some(subject), "{}", name);
+            assert!(message.header("From").unwrap().contains(from), "{}", name);
+            let text = message.plain_text().unwrap();
+            assert!(text.contains(body), "{}: {:?}", name, text);
+        }
+
+        // 只有 HTML 正文的邮件
+        let message = parse(&fixture("html_only.emlx"));
+        assert!(message.plain_text().is_none());
+        assert!(message.html().unwrap().contains(">39281047</p>"));
+    }
+
+    #[test]
+    fn test_transfer_encodings_and_encoded_words() {
+        assert_eq!(decode_base64(b"NDgy\r\nOTEz"), b"482913");
+        assert_eq!(
+            decode_quoted_printable(b"code=3D=\r\n482913 =E9=AA=8C"),
+            "code=482913 验".as_bytes()
+        );
+        assert_eq!(
+            decode_encoded_words("=?UTF-8?B?6aqM6K+B?= =?UTF-8?B?56CB?= 482913"),
+            "验证码 482913"
+        );
+        // 多字节字符被拆到两个编码字中
+        assert_eq!(
+            decode_encoded_words("=?UTF-8?Q?=E9=AA?= =?UTF-8?Q?=8C_code?="),
+            "验 code"
+        );
+        assert_eq!(decode_encoded_words("Hi =?bad"), "Hi =?bad");
+
+        let from = Mailbox::parse("\"Slack\" <no-reply@slack.com>").unwrap();
+        assert_eq!(from.name.as_deref(), Some("Slack"));
+        assert_eq!(from.to_string(), "Slack <no-reply@slack.com>");
+        let from = Mailbox::parse("no-reply@slack.com (Slack)").unwrap();
+        assert_eq!(from.display_name(), "Slack");
+        let from = Mailbox::parse("<no-reply@slack.com>").unwrap();
+        assert_eq!(from.display_name(), "no-reply@slack.com");
+        assert!(Mailbox::parse("undisclosed recipients").is_none());
+
+        // 未声明字符集的 8 位正文按 GBK 解码
+        let mut raw = b"Content-Type: text/plain\n\n".to_vec();
+        raw.extend(GB18030.encode("验证码 482913").0.iter());
+        assert_eq!(parse(&raw).plain_text().as_deref(), Some("验证码 482913"));
+    }
+
+    proptest! {
+        // 邮件内容来自外部，任意字节都不能让监听线程 panic
+        #[test]
+        fn prop_mime_parsing_never_panics(raw in any::<Vec<u8>>()) {
+            parse(&raw).plain_text();
+        }
+
+        // text/plain 部分中的验证码总能被取出，text/html 部分的内容不会混进来
+        #[test]
+        fn prop_plain_text_part_is_recovered(
+            code in "[0-9]{4,8}",
+            boundary in "[A-Za-z0-9_.]{1,30}",
+            html in "[a-z<>/ ]{0,40}",
+        ) {
+            let raw = format!(
+                "Content-Type: multipart/alternative; boundary=\"{b}\"\n\n--{b}\nContent-Type: text/plain; charset=UTF-8\n\nYour code is {code}\n--{b}\nContent-Type: text/html; charset=UTF-8\n\n<p>{html}</p>\n--{b}--",
+                b = boundary,
+                code = code,
+                html = html,
+            );
+            let text = parse(raw.as_bytes()).plain_text();
+            prop_assert_eq!(text, Some(format!("Your code is {}", code)));
+        }
+    }
+}
@@ -1,10 +1,14 @@
 pub mod actor;
 pub mod commands;
 pub mod email;
+pub mod html;
+pub mod mailboxes;
 pub mod message;
+pub mod mime;
 pub mod watcher;
 
 use crate::config::Config;
+use crate::parser::SharedExtractor;
 use actor::MonitorActor;
 use commands::MonitorCommand;
 use rust_i18n::t;
@@ -12,7 +16,8 @@ use tokio::sync::mpsc;
 
 pub fn start_monitoring_actor() -> mpsc::Sender<MonitorCommand> {
     let (sender, receiver) = mpsc::channel(32);
-    let mut actor = MonitorActor::new(receiver);
+    // 提取器在两个处理器之间共享，只在配置文件变化时重建
+    let mut actor = MonitorActor::new(receiver, SharedExtractor::load());
     let sender_clone = sender.clone();
 
     tokio::spawn(async move {
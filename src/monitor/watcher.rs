@@ -18,6 +18,8 @@ pub trait FileProcessor: Clone + Send + Sync + 'static {
 pub struct FileWatcher<P: FileProcessor> {
     processor: P,
     watcher_task: Option<JoinHandle<()>>,
+    // 启动后正在监听的路径
+    watch_path: Option<PathBuf>,
 }
 
 fn async_watcher() -> notify::Result<(RecommendedWatcher, Receiver<notify::Result<Event>>)> {
@@ -38,9 +40,15 @@ impl<P: FileProcessor> FileWatcher<P> {
         Self {
             processor,
             watcher_task: None,
+            watch_path: None,
         }
     }
 
+    /// 正在监听的路径，未启动时为 None
+    pub fn current_watch_path(&self) -> Option<&Path> {
+        self.watch_path.as_deref()
+    }
+
     pub fn start(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let path = self.processor.get_watch_path();
         let recursive_mode = self.processor.get_recursive_mode();
@@ -48,6 +56,7 @@ impl<P: FileProcessor> FileWatcher<P> {
         let processor = self.processor.clone();
 
         info!("Starting watcher for: {:?}", path);
+        self.watch_path = Some(path.clone());
 
         let task = tokio::spawn(async move {
             if let Err(e) = Self::watch_path(path, recursive_mode, pattern, processor).await {
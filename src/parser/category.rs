@@ -0,0 +1,199 @@
+use crate::config::CategorySwitches;
+
+use super::keywords::find_word;
+use super::normalize::MappedText;
+
+// 非验证码类别的特征词，英文特征词按完整单词匹配
+const CATEGORY_MARKERS: &[(MessageCategory, &[&str])] = &[
+    (
+        MessageCategory::PickupCode,
+        &[
+            "取件码",
+            "取货码",
+            "提货码",
+            "取件号",
+            "丰巢",
+            "快递柜",
+            "驿站",
+            "菜鸟",
+            "pickup code",
+            "parcel locker",
+        ],
+    ),
+    (
+        MessageCategory::Coupon,
+        &[
+            "优惠码",
+            "优惠券",
+            "兑换码",
+            "折扣码",
+            "coupon",
+            "promo code",
+            "discount code",
+        ],
+    ),
+    (
+        MessageCategory::BookingReference,
+        &[
+            "取票号",
+            "订票",
+            "预订",
+            "预约号",
+            // 单独的 "booking"、"reservation" 会命中 "Booking.com"、"reservation code" 等验证码消息
+            "booking reference",
+            "booking number",
+            "booking confirmation",
+            "reservation number",
+            "confirmation number",
+            "e-ticket",
+        ],
+    ),
+];
+
+// 支付验证码的特征词，只在消息没有被判为其他类别时生效
+const PAYMENT_MARKERS: &[&str] = &[
+    "支付",
+    "付款",
+    "交易",
+    "转账",
+    "消费",
+    "扣款",
+    "payment",
+    "transaction",
+    "purchase",
+];
+
+// 明确提到验证码的消息，即使带有取件码、优惠码、预订等字样也按验证码处理
+const OTP_MARKERS: &[&str] = &[
+    "验证码",
+    "校验码",
+    "动态码",
+    "verification code",
+    "security code",
+    "one-time",
+    "login code",
+];
+
+// 取件码消息中作为锚点的关键词
+const PICKUP_KEYWORDS: &[&str] = &["取件码", "取货码", "提货码", "取件号", "pickup code"];
+
+/// 取件码消息额外使用的关键词
+pub fn pickup_keywords() -> Vec<String> {
+    PICKUP_KEYWORDS.iter().map(|k| k.to_string()).collect()
+}
+
+/// 消息的类别，决定识别出的代码如何处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageCategory {
+    /// 登录验证码，默认类别
+    LoginOtp,
+    /// 支付、转账验证码
+    PaymentOtp,
+    /// 快递柜、驿站取件码
+    PickupCode,
+    /// 车票、酒店等预订号
+    BookingReference,
+    /// 优惠码、兑换码
+    Coupon,
+}
+
+impl MessageCategory {
+    /// 该类别是否在配置中启用
+    pub fn is_enabled(&self, switches: &CategorySwitches) -> bool {
+        match self {
+            MessageCategory::LoginOtp => switches.login_otp,
+            MessageCategory::PaymentOtp => switches.payment_otp,
+            MessageCategory::PickupCode => switches.pickup_code,
+            MessageCategory::BookingReference => switches.booking_reference,
+            MessageCategory::Coupon => switches.coupon,
+        }
+    }
+
+    /// 取件码等类别的代码不自动输入，而是记入历史
+    pub fn is_otp(&self) -> bool {
+        matches!(
+            self,
+            MessageCategory::LoginOtp | MessageCategory::PaymentOtp
+        )
+    }
+}
+
+/// 根据特征词判断消息类别，没有命中任何特征词时视为登录验证码。
+///
+/// 提到验证码的消息、同时命中多个非验证码类别的消息都按验证码处理，宁可多输入一次也不漏掉验证码。
+pub fn classify(text: &str) -> MessageCategory {
+    let text = MappedText::lowercase(text);
+    let mentions = |markers: &[&str]| {
+        markers
+            .iter()
+            .any(|marker| !find_word(&text, marker).is_empty())
+    };
+    let otp_category = if mentions(PAYMENT_MARKERS) {
+        MessageCategory::PaymentOtp
+    } else {
+        MessageCategory::LoginOtp
+    };
+    if mentions(OTP_MARKERS) {
+        return otp_category;
+    }
+
+    let mut matched = CATEGORY_MARKERS
+        .iter()
+        .filter(|(_, markers)| mentions(markers))
+        .map(|(category, _)| *category);
+    match (matched.next(), matched.next()) {
+        (Some(category), None) => category,
+        _ => otp_category,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_message_category() {
+        let cases = vec![
+            (
+                "【丰巢】凭取件码 12345678 到丰巢柜取件",
+                MessageCategory::PickupCode,
+            ),
+            (
+                "【菜鸟驿站】您的包裹已到站，取件码 3-2-1045",
+                MessageCategory::PickupCode,
+            ),
+            ("您正在进行支付，验证码 482913", MessageCategory::PaymentOtp),
+            ("Use coupon SAVE20 for 20% off", MessageCategory::Coupon),
+            (
+                "Your booking reference is K7Q2XP",
+                MessageCategory::BookingReference,
+            ),
+            ("您的登录验证码为 482913", MessageCategory::LoginOtp),
+            (
+                "Use promo code SAVE20 for 20% off. Your verification code is 482913",
+                MessageCategory::LoginOtp,
+            ),
+            (
+                "【菜鸟】您的登录验证码为 482913，5分钟内有效",
+                MessageCategory::LoginOtp,
+            ),
+            ("Your Booking.com code is 482913", MessageCategory::LoginOtp),
+            (
+                "Your Airbnb reservation code: 482913",
+                MessageCategory::LoginOtp,
+            ),
+            (
+                "Your coupons are ready, code 482913",
+                MessageCategory::LoginOtp,
+            ),
+            (
+                "【丰巢】取件码 12345678，另赠您一张优惠券",
+                MessageCategory::LoginOtp,
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(classify(input), expected, "input: {:?}", input);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use super::otp_model::OtpModel;
+use super::strategies::{self, KeywordProximityExtractor};
+use super::{ExtractionResult, LabeledCode, MessageCategory, SignInLink};
+use super::{category, classifier, service};
+use crate::config::Config;
+use std::sync::{Arc, OnceLock};
+
+/// 交给提取策略的一条消息，服务名称和类别由 [`ExtractorChain`] 预先算好
+#[derive(Debug, Clone)]
+pub struct Message<'a> {
+    pub content: &'a str,
+    /// iMessage 的 handle 或邮件的 From 头
+    pub sender: Option<&'a str>,
+    /// 识别出的服务名称，见 [`super::service::identify_service`]
+    pub service: Option<String>,
+    pub category: MessageCategory,
+    /// HTML 邮件中加粗、大号或等宽字体的短文本，见 [`strategies::HtmlEmphasisExtractor`]
+    pub emphasized: &'a [String],
+    /// 邮件主题，位于 `content` 开头，见 [`strategies::SubjectExtractor`]
+    pub subject: Option<&'a str>,
+    /// 不带发件人规则的关键词距离评分结果，同一条消息只计算一次，
+    /// 由 [`strategies::KeywordProximityExtractor`] 和 [`strategies::HtmlEmphasisExtractor`] 共用
+    pub(super) keyword_result: OnceLock<Option<ExtractionResult>>,
+}
+
+impl<'a> Message<'a> {
+    pub fn new(content: &'a str, sender: Option<&'a str>) -> Self {
+        Self {
+            content,
+            sender,
+            service: service::identify_service(content, sender),
+            category: category::classify(content),
+            emphasized: &[],
+            subject: None,
+            keyword_result: OnceLock::new(),
+        }
+    }
+
+    pub fn with_emphasized(mut self, emphasized: &'a [String]) -> Self {
+        self.emphasized = emphasized;
+        self
+    }
+
+    /// `subject` 必须是 `content` 的开头，否则被忽略
+    pub fn with_subject(mut self, subject: &'a str) -> Self {
+        self.subject = Some(subject).filter(|s| !s.is_empty() && self.content.starts_with(s));
+        self
+    }
+}
+
+/// 提取策略的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Extraction {
+    Code(Box<ExtractionResult>),
+    SignInLink(SignInLink),
+}
+
+/// 提取策略，由 [`ExtractorChain`] 按配置中 `[chain] order` 的顺序依次运行。
+///
+/// 内置策略见 [`super::strategies`]；自定义策略可以通过 [`ExtractorChain::with_strategies`] 加入。
+pub trait CodeExtractor: Send + Sync {
+    /// 策略名称，用于配置和日志
+    fn name(&self) -> &str;
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction>;
+
+    /// 是否会给出验证码。消息被判断为不接受验证码时（类别被关闭或被分类模型拒绝），
+    /// 这类策略不再运行；只检测登录链接的策略应返回 false
+    fn yields_codes(&self) -> bool {
+        true
+    }
+
+    /// 是否以本策略的结果为准、不再运行后面的策略。
+    /// 默认为否，只有验证码的置信度达到 `short_circuit_confidence` 时才提前结束
+    fn is_decisive(&self, _message: &Message<'_>, _extraction: Option<&Extraction>) -> bool {
+        false
+    }
+}
+
+impl<T: CodeExtractor + ?Sized> CodeExtractor for Arc<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+        (**self).extract(message)
+    }
+
+    fn yields_codes(&self) -> bool {
+        (**self).yields_codes()
+    }
+
+    fn is_decisive(&self, message: &Message<'_>, extraction: Option<&Extraction>) -> bool {
+        (**self).is_decisive(message, extraction)
+    }
+}
+
+/// 根据配置构建的提取策略链。
+///
+/// 策略和其中的正则、关键词在构建时编译一次，之后每条消息直接复用；配置变化时需要重新构建，
+/// 见 [`super::SharedExtractor`]。
+pub struct ExtractorChain {
+    config: Config,
+    // 启用时加载的验证码消息分类模型
+    classifier: Option<OtpModel>,
+    strategies: Vec<Box<dyn CodeExtractor>>,
+}
+
+impl ExtractorChain {
+    /// 按 `[chain] order` 构建内置策略，未知的策略名称记录警告后忽略
+    pub fn new(config: &Config) -> Self {
+        let pipeline = Arc::new(KeywordProximityExtractor::new(config));
+        let strategies = config
+            .chain
+            .order
+            .iter()
+            .filter_map(|name| {
+                let strategy = strategies::builtin(name, config, &pipeline);
+                if strategy.is_none() {
+                    log::warn!("Unknown extraction strategy: {}", name);
+                }
+                strategy
+            })
+            .collect();
+        Self::with_strategies(config, strategies)
+    }
+
+    /// 使用给定的策略，忽略 `[chain] order`
+    pub fn with_strategies(config: &Config, strategies: Vec<Box<dyn CodeExtractor>>) -> Self {
+        Self {
+            config: config.clone(),
+            classifier: config.classifier.enabled.then(classifier::load_model),
+            strategies,
+        }
+    }
+
+    /// 构建该提取器时使用的配置
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// 依次运行的策略名称
+    pub fn strategy_names(&self) -> Vec<&str> {
+        self.strategies.iter().map(|s| s.name()).collect()
+    }
+
+    /// 依次运行各策略。多个策略给出同一验证码时合并置信度，最后取置信度最高的验证码；
+    /// 没有验证码时返回检测到的一键登录链接。
+    pub fn run(&self, content: &str, sender: Option<&str>) -> Option<Extraction> {
+        self.run_message(&Message::new(content, sender))
+    }
+
+    /// 同 [`run`](Self::run)，消息带有邮件中的强调文字等额外信息时使用
+    pub fn run_message(&self, message: &Message<'_>) -> Option<Extraction> {
+        let accepts_codes = self.accepts_codes(message);
+
+        let mut codes: Vec<ExtractionResult> = Vec::new();
+        let mut link = None;
+        for strategy in &self.strategies {
+            if !accepts_codes && strategy.yields_codes() {
+                continue;
+            }
+            let extraction = strategy.extract(message);
+            let decisive = strategy.is_decisive(message, extraction.as_ref());
+            match extraction {
+                Some(Extraction::Code(result)) => {
+                    let confidence = merge_code(&mut codes, *result, strategy.name());
+                    if confidence >= self.config.chain.short_circuit_confidence {
+                        break;
+                    }
+                }
+                Some(Extraction::SignInLink(found)) => {
+                    link.get_or_insert(found);
+                }
+                None => {}
+            }
+            if decisive {
+                break;
+            }
+        }
+
+        // 置信度相同时保留先运行的策略给出的验证码
+        let best = codes
+            .into_iter()
+            .reduce(|best, result| {
+                if result.confidence > best.confidence {
+                    result
+                } else {
+                    best
+                }
+            })
+            .map(|result| Extraction::Code(Box::new(result)));
+        best.or(link.map(Extraction::SignInLink))
+    }
+
+    // 类别被关闭或分类模型判断为非验证码消息时，给出验证码的策略都不运行
+    fn accepts_codes(&self, message: &Message<'_>) -> bool {
+        if !message.category.is_enabled(&self.config.categories) {
+            log::debug!(
+                "Skipping message in disabled category {:?}",
+                message.category
+            );
+            return false;
+        }
+
+        let probability = self
+            .classifier
+            .as_ref()
+            .and_then(|model| model.otp_probability(message.content));
+        if let Some(probability) = probability.filter(|p| *p < self.config.classifier.threshold) {
+            log::debug!("Classifier rejected message (p = {:.3})", probability);
+            return false;
+        }
+        true
+    }
+
+    /// 从消息中提取验证码。`sender` 为 iMessage 的 handle 或邮件的 From 头，
+    /// 用于匹配 `[[rules]]` 中的发件人规则。
+    pub fn extract(&self, content: &str, sender: Option<&str>) -> Option<ExtractionResult> {
+        self.extract_message(&Message::new(content, sender))
+    }
+
+    pub fn extract_message(&self, message: &Message<'_>) -> Option<ExtractionResult> {
+        match self.run_message(message)? {
+            Extraction::Code(result) => Some(*result),
+            Extraction::SignInLink(_) => None,
+        }
+    }
+
+    /// 返回消息中所有可能的验证码及其用途，第一个是 [`extract`](Self::extract) 选中的验证码
+    pub fn extract_all(&self, content: &str, sender: Option<&str>) -> Vec<LabeledCode> {
+        let Some(result) = self.extract(content, sender) else {
+            return Vec::new();
+        };
+        let best = LabeledCode {
+            code: result.code,
+            formatted: result.formatted,
+            span: result.span,
+            role: result.role,
+            confidence: result.confidence,
+        };
+        std::iter::once(best).chain(result.alternates).collect()
+    }
+}
+
+// 把一个策略的结果并入已有结果，返回该验证码合并后的置信度
+fn merge_code(
+    codes: &mut Vec<ExtractionResult>,
+    mut result: ExtractionResult,
+    strategy: &str,
+) -> f32 {
+    if let Some(existing) = codes.iter_mut().find(|c| c.code == result.code) {
+        // 相互独立的证据：1 - (1 - a)(1 - b)
+        existing.confidence = 1.0 - (1.0 - existing.confidence) * (1.0 - result.confidence);
+        existing.strategies.push(strategy.to_string());
+        return existing.confidence;
+    }
+
+    result.strategies = vec![strategy.to_string()];
+    let confidence = result.confidence;
+    codes.push(result);
+    confidence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::CodeRole;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // 总是给出固定验证码的策略
+    struct Fixed {
+        name: &'static str,
+        code: &'static str,
+        confidence: f32,
+    }
+
+    impl CodeExtractor for Fixed {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+            Some(Extraction::Code(Box::new(ExtractionResult {
+                code: self.code.to_string(),
+                formatted: self.code.to_string(),
+                span: (0, 0),
+                keyword: String::new(),
+                distance: 0,
+                score: 0.0,
+                rejected: Vec::new(),
+                masked: Vec::new(),
+                rule: None,
+                strategies: Vec::new(),
+                service: message.service.clone(),
+                category: message.category,
+                confidence: self.confidence,
+                role: CodeRole::VerificationCode,
+                alternates: Vec::new(),
+                validity: None,
+            })))
+        }
+    }
+
+    // 记录被调用次数的策略
+    struct Counting {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl CodeExtractor for Counting {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn extract(&self, _message: &Message<'_>) -> Option<Extraction> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            None
+        }
+    }
+
+    fn fixed(name: &'static str, code: &'static str, confidence: f32) -> Box<dyn CodeExtractor> {
+        Box::new(Fixed {
+            name,
+            code,
+            confidence,
+        })
+    }
+
+    #[test]
+    fn test_chain_merges_and_short_circuits() {
+        let config = Config::default();
+        let message = "您的验证码为 482913";
+
+        // 两个策略都给出 482913，合并后的置信度超过单独给出 111111 的策略
+        let chain = ExtractorChain::with_strategies(
+            &config,
+            vec![
+                fixed("a", "482913", 0.6),
+                fixed("b", "111111", 0.7),
+                fixed("c", "482913", 0.5),
+            ],
+        );
+        let result = chain.extract(message, None).unwrap();
+        assert_eq!(result.code, "482913");
+        assert!((result.confidence - 0.8).abs() < 1e-6);
+        assert_eq!(result.strategies, vec!["a", "c"]);
+
+        // 达到 short_circuit_confidence 后不再运行后面的策略
+        let chain = ExtractorChain::with_strategies(
+            &config,
+            vec![fixed("a", "111111", 0.95), fixed("b", "482913", 0.99)],
+        );
+        assert_eq!(chain.extract(message, None).unwrap().code, "111111");
+
+        // 关闭的类别中不运行给出验证码的策略
+        let mut disabled = config.clone();
+        disabled.categories.login_otp = false;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let chain = ExtractorChain::with_strategies(
+            &disabled,
+            vec![Box::new(Counting {
+                calls: calls.clone(),
+            })],
+        );
+        assert!(chain.extract(message, None).is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_chain_order_from_config() {
+        let mut config = Config::default();
+        assert_eq!(
+            ExtractorChain::new(&config).strategy_names(),
+            vec![
+                "learned_template",
+                "sender_rule",
+                "email_subject",
+                "keyword_proximity",
+                "html_emphasis",
+                "sign_in_link"
+            ]
+        );
+
+        // 去掉关键词策略后只剩链接检测；未知的名称被忽略
+        config.chain.order = vec!["sign_in_link".to_string(), "no_such_strategy".to_string()];
+        let chain = ExtractorChain::new(&config);
+        assert_eq!(chain.strategy_names(), vec!["sign_in_link"]);
+        assert!(chain.extract("您的验证码为 482913", None).is_none());
+        match chain.run("Click to sign in: https://slack.com/z/abc123", None) {
+            Some(Extraction::SignInLink(link)) => {
+                assert_eq!(link.url, "https://slack.com/z/abc123")
+            }
+            other => panic!("expected a sign-in link, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::corpus::{self, CorpusCase};
+use super::otp_model::OtpModel;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// build.rs 用 tests/corpus 训练出的内置模型
+const EMBEDDED_MODEL: &str = include_str!(concat!(env!("OUT_DIR"), "/otp_model.toml"));
+
+/// 本地重新训练后的模型，存在时代替内置模型
+pub fn get_model_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_default()
+        .join("messauto")
+        .join("otp_model.toml")
+}
+
+/// 用户纠正过的样本，格式与 tests/corpus 相同
+pub fn get_corrections_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_default()
+        .join("messauto")
+        .join("corrections")
+}
+
+pub fn embedded_model() -> OtpModel {
+    toml::from_str(EMBEDDED_MODEL).unwrap_or_else(|e| {
+        log::error!("Failed to parse embedded OTP model: {}", e);
+        OtpModel::default()
+    })
+}
+
+/// 优先使用本地训练的模型，读取失败时退回内置模型
+pub fn load_model() -> OtpModel {
+    let path = get_model_path();
+    if path.exists() {
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| toml::from_str(&content).map_err(|e| e.to_string()))
+        {
+            Ok(model) => return model,
+            Err(e) => log::warn!("Failed to load OTP model {:?}, using embedded: {}", path, e),
+        }
+    }
+    embedded_model()
+}
+
+/// 在内置模型上累加新的样本
+pub fn train(cases: &[CorpusCase]) -> OtpModel {
+    let mut model = embedded_model();
+    for case in cases {
+        model.add(&case.message, case.expected.is_some());
+    }
+    model
+}
+
+/// 用纠正过的样本重新训练并保存，返回保存路径和使用的样本数
+pub fn retrain(corrections: &Path) -> Result<(PathBuf, usize), Box<dyn std::error::Error>> {
+    let cases = if corrections.exists() {
+        corpus::load_dir(corrections)?
+    } else {
+        Vec::new()
+    };
+    let model = train(&cases);
+
+    let path = get_model_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, toml::to_string(&model)?)?;
+    Ok((path, cases.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser::ExtractorChain;
+
+    fn classifier_extractor() -> ExtractorChain {
+        let mut config = Config::default();
+        config.classifier.enabled = true;
+        ExtractorChain::new(&config)
+    }
+
+    fn failures(extractor: &ExtractorChain, cases: &[CorpusCase]) -> Vec<(String, Option<String>)> {
+        corpus::run(extractor, cases)
+            .into_iter()
+            .filter(|outcome| !outcome.passed())
+            .map(|outcome| (outcome.case.message.clone(), outcome.actual))
+            .collect()
+    }
+
+    #[test]
+    fn test_classifier_rejects_known_marketing() {
+        let model = embedded_model();
+        assert!(model.otp_messages > 0 && model.other_messages > 0);
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+        let cases = corpus::load_dir(&dir).unwrap();
+        let (marketing, others): (Vec<_>, Vec<_>) =
+            cases.into_iter().partition(|case| case.known_failure);
+        assert!(!marketing.is_empty());
+
+        // 关键词规则无法排除的营销短信，开启分类模型后不应识别出验证码
+        let extractor = classifier_extractor();
+        for case in &marketing {
+            assert_eq!(
+                extractor.extract(&case.message, case.sender.as_deref()),
+                None,
+                "{}",
+                case.message
+            );
+        }
+        // 其余样本不受影响
+        let failures = failures(&extractor, &others);
+        assert!(failures.is_empty(), "failures: {:#?}", failures);
+    }
+
+    #[test]
+    fn test_classifier_on_held_out_cases() {
+        // build.rs 不读取子目录，这些样本没有参与内置模型的训练
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/holdout");
+        let cases = corpus::load_dir(&dir).unwrap();
+        assert!(cases.iter().any(|case| case.expected.is_none()));
+        assert!(cases.iter().any(|case| case.expected.is_some()));
+
+        let failures = failures(&classifier_extractor(), &cases);
+        assert!(failures.is_empty(), "failures: {:#?}", failures);
+    }
+}
@@ -0,0 +1,249 @@
+use super::ExtractorChain;
+use super::keyword_packs::{self, Script};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// 日志中消息原文前的固定前缀，见 monitor::message 和 monitor::email 中的日志
+const LOG_MESSAGE_PREFIXES: &[&str] = &["Processing message ", "邮件内容:", "mail content"];
+
+/// 回归语料中的一条样本
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CorpusCase {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+    /// 期望识别出的验证码，省略表示不应识别出验证码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    pub language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// 已知暂未解决的样本，只报告结果，不计为回归
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub known_failure: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct CorpusFile {
+    #[serde(default)]
+    cases: Vec<CorpusCase>,
+}
+
+/// 单条样本的运行结果
+#[derive(Debug)]
+pub struct CaseOutcome<'a> {
+    pub case: &'a CorpusCase,
+    pub actual: Option<String>,
+}
+
+impl CaseOutcome<'_> {
+    pub fn passed(&self) -> bool {
+        self.actual == self.case.expected
+    }
+}
+
+/// 按语言统计的正确率
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LanguageStats {
+    pub total: usize,
+    pub passed: usize,
+}
+
+impl LanguageStats {
+    pub fn accuracy(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.passed as f64 / self.total as f64
+        }
+    }
+}
+
+/// 读取目录下所有 .toml 语料文件，按文件名排序
+pub fn load_dir(dir: &Path) -> Result<Vec<CorpusCase>, Box<dyn std::error::Error>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    let mut cases = Vec::new();
+    for path in paths {
+        let file: CorpusFile = toml::from_str(&fs::read_to_string(&path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        cases.extend(file.cases);
+    }
+    Ok(cases)
+}
+
+/// 用给定的提取器运行所有样本
+pub fn run<'a>(extractor: &ExtractorChain, cases: &'a [CorpusCase]) -> Vec<CaseOutcome<'a>> {
+    cases
+        .iter()
+        .map(|case| CaseOutcome {
+            case,
+            actual: extractor
+                .extract(&case.message, case.sender.as_deref())
+                .map(|r| r.code),
+        })
+        .collect()
+}
+
+/// 按语言汇总正确率
+pub fn stats_by_language(outcomes: &[CaseOutcome]) -> BTreeMap<String, LanguageStats> {
+    let mut stats: BTreeMap<String, LanguageStats> = BTreeMap::new();
+    for outcome in outcomes {
+        let entry = stats.entry(outcome.case.language.clone()).or_default();
+        entry.total += 1;
+        if outcome.passed() {
+            entry.passed += 1;
+        }
+    }
+    stats
+}
+
+/// 打印各语言的正确率和结果与期望不符的样本，返回回归的样本数。
+///
+/// 标记为 `known_failure` 的样本失败时不计为回归，通过时提示可以去掉标记。
+pub fn report(outcomes: &[CaseOutcome]) -> usize {
+    println!("=== 验证码提取正确率测试结果 ===");
+    for (language, stats) in stats_by_language(outcomes) {
+        println!(
+            "{}: {}/{} ({:.2}%)",
+            language,
+            stats.passed,
+            stats.total,
+            stats.accuracy() * 100.0
+        );
+    }
+
+    let mut regressions = 0;
+    for outcome in outcomes
+        .iter()
+        .filter(|o| o.passed() == o.case.known_failure)
+    {
+        if outcome.case.known_failure {
+            println!("已知失败的样本现在可以通过，可去掉 known_failure:");
+        } else {
+            regressions += 1;
+            println!("失败案例:");
+        }
+        println!("输入: {:?}", outcome.case.message);
+        println!("期望: {:?}", outcome.case.expected);
+        println!("实际: {:?}", outcome.actual);
+        if let Some(notes) = &outcome.case.notes {
+            println!("备注: {}", notes);
+        }
+        println!("---");
+    }
+    regressions
+}
+
+/// 从日志行中取出消息原文，去掉时间戳、级别等前缀。
+///
+/// 支持 "Processing message 0: ..." 和 "邮件内容:..." 两种日志，其他行原样返回去掉前缀后的内容。
+pub fn message_from_log_line(line: &str) -> String {
+    let line = line.trim();
+    // "[2025-08-21 00:47:22.775 INFO messauto::monitor::email] ..."
+    let line = match line
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+    {
+        Some((_, rest)) => rest,
+        None => line,
+    };
+
+    for prefix in LOG_MESSAGE_PREFIXES {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            // "Processing message 0: " 中还有序号
+            return match rest.split_once(": ") {
+                Some((index, message)) if index.chars().all(|c| c.is_ascii_digit()) => {
+                    message.to_string()
+                }
+                _ => rest.to_string(),
+            };
+        }
+    }
+    line.to_string()
+}
+
+/// 根据消息使用的文字猜测样本语言，拉丁字母的语言无法区分，按英文处理
+pub fn detect_language(message: &str) -> &'static str {
+    match keyword_packs::detect_script(message) {
+        Some(Script::LatinExtended) => "en",
+        Some(script) => keyword_packs::for_script(script)
+            .next()
+            .map_or("en", |pack| pack.language),
+        None if message
+            .chars()
+            .any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c)) =>
+        {
+            "zh-CN"
+        }
+        None => "en",
+    }
+}
+
+/// 把样本追加到 `<dir>/<language>.toml` 末尾，保留文件中已有的注释
+pub fn append_case(dir: &Path, case: &CorpusCase) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.toml", case.language));
+    let entry = toml::to_string(&CorpusFile {
+        cases: vec![case.clone()],
+    })?;
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file)?;
+    file.write_all(entry.as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_line_and_append_case() {
+        assert_eq!(
+            message_from_log_line(
+                "[2025-08-21 00:47:22.775 INFO messauto::monitor::message] Processing message 0: 您的验证码为 482913"
+            ),
+            "您的验证码为 482913"
+        );
+        assert_eq!(
+            message_from_log_line(
+                "[2025-08-21 00:47:22.775 INFO messauto::monitor::email] 邮件内容:code 723333"
+            ),
+            "code 723333"
+        );
+        assert_eq!(
+            message_from_log_line("your code is 432141"),
+            "your code is 432141"
+        );
+        assert_eq!(detect_language("您的验证码为 482913"), "zh-CN");
+        assert_eq!(detect_language("認証番号：482913 を入力してください"), "ja");
+        assert_eq!(detect_language("Ваш код: 482913"), "ru");
+
+        let dir = std::env::temp_dir().join(format!("messauto-corpus-{}", std::process::id()));
+        let case = CorpusCase {
+            message: "Your code is \"482913\"".to_string(),
+            sender: Some("Slack <no-reply@slack.com>".to_string()),
+            expected: Some("482913".to_string()),
+            language: "en".to_string(),
+            notes: None,
+            known_failure: false,
+        };
+        let path = append_case(&dir, &case).unwrap();
+        append_case(&dir, &case).unwrap();
+        let loaded = load_dir(&dir).unwrap();
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_dir(&dir);
+        assert_eq!(loaded, vec![case.clone(), case]);
+    }
+}
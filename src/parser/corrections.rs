@@ -0,0 +1,354 @@
+use super::corpus::{self, CorpusCase};
+use super::normalize::MappedText;
+use super::{ExtractionResult, SharedExtractor};
+use super::{classifier, rules};
+use crate::config::SenderRule;
+use fancy_regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// 验证码前后用作锚点的文字最多取多少个字符
+const MAX_ANCHOR_CHARS: usize = 12;
+
+/// 悬浮窗中"验证码不对"需要的信息，由监听进程通过标准输入传给悬浮窗进程，见 [`crate::ipc`]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct PendingCorrection {
+    pub message: String,
+    pub sender: Option<String>,
+    pub service: Option<String>,
+    /// 提取器选中的验证码
+    pub code: String,
+    /// 消息中其他可能的验证码，供用户选择
+    pub candidates: Vec<CorrectionCandidate>,
+}
+
+/// 可供改选的验证码及其在消息原文中的字节区间
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CorrectionCandidate {
+    pub code: String,
+    pub span: (usize, usize),
+}
+
+impl PendingCorrection {
+    /// 备选验证码、被淘汰的候选码和被遮盖的短编号（如订单号）都可能是正确的验证码，去重后按出现顺序排列
+    pub fn from_result(message: &str, sender: Option<&str>, result: &ExtractionResult) -> Self {
+        let mut spans: Vec<((usize, usize), &str)> = result
+            .alternates
+            .iter()
+            .map(|alternate| (alternate.span, alternate.code.as_str()))
+            .chain(
+                result
+                    .rejected
+                    .iter()
+                    .filter(|candidate| candidate.code.chars().any(|c| c.is_ascii_digit()))
+                    .map(|candidate| (candidate.span, candidate.code.as_str())),
+            )
+            .chain(result.masked.iter().filter_map(|entity| {
+                let text = message.get(entity.span.0..entity.span.1)?;
+                let plausible = (4..=12).contains(&text.len())
+                    && text.chars().any(|c| c.is_ascii_digit())
+                    && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+                plausible.then_some((entity.span, text))
+            }))
+            .collect();
+        spans.sort();
+
+        let mut candidates: Vec<CorrectionCandidate> = Vec::new();
+        for (span, code) in spans {
+            if code != result.code && !candidates.iter().any(|c| c.code == code) {
+                candidates.push(CorrectionCandidate {
+                    code: code.to_string(),
+                    span,
+                });
+            }
+        }
+
+        Self {
+            message: message.to_string(),
+            sender: sender.map(str::to_string),
+            service: result.service.clone(),
+            code: result.code.clone(),
+            candidates,
+        }
+    }
+}
+
+/// 根据用户选择的验证码生成发件人规则：以验证码前（或后）紧挨着的文字为锚点，
+/// 按验证码在消息中的书写形式（分组、长度和字符类型）生成正则。
+///
+/// `span` 为所选验证码在原文中的字节区间。提取器在归一化后的文本上运行，锚点和正则也按归一化后的文字生成；
+/// 生成的规则会先在原消息上验证，不能在同一位置提取出该验证码时返回 None。
+pub fn learn_rule(
+    message: &str,
+    sender: Option<&str>,
+    service: Option<&str>,
+    span: (usize, usize),
+) -> Option<SenderRule> {
+    let (start, end) = span;
+    let before = MappedText::normalize(message.get(..start)?).text;
+    let formatted = MappedText::normalize(message.get(start..end)?).text;
+    let after = MappedText::normalize(message.get(end..)?).text;
+    if formatted.is_empty() {
+        return None;
+    }
+    let code_pattern = format!("({})", formatted_pattern(&formatted));
+
+    let (pattern, anchor) = match anchor_before(&before) {
+        Some(anchor) => (
+            format!(r"{}\s*{}", fancy_regex::escape(&anchor), code_pattern),
+            anchor,
+        ),
+        None => {
+            let anchor = anchor_after(&after)?;
+            (
+                format!(r"{}\s*{}", code_pattern, fancy_regex::escape(&anchor)),
+                anchor,
+            )
+        }
+    };
+
+    let normalized = format!("{}{}{}", before, formatted, after);
+    let learned = Regex::new(&pattern)
+        .ok()?
+        .captures(&normalized)
+        .ok()??
+        .get(1)
+        .map(|m| m.start() == before.len() && m.as_str() == formatted);
+    if learned != Some(true) {
+        return None;
+    }
+
+    // 邮件的 From 头 "Name <addr>" 只保留地址
+    let sender = sender.map(|s| rules::sender_address(s).to_string());
+    let brand = match sender {
+        Some(_) => None,
+        None => Some(service?.to_string()),
+    };
+    let key = sender.as_deref().or(brand.as_deref())?;
+
+    Some(SenderRule {
+        name: Some(format!("learned:{}:{}", key, anchor)),
+        sender,
+        brand,
+        regex: Some(pattern),
+        group: Some(1),
+        keywords: Some(vec![anchor]),
+        learned: true,
+        ..SenderRule::default()
+    })
+}
+
+// 按书写形式生成验证码的正则：字母数字按段限定长度，分隔符原样保留，
+// 如 "123 456" -> "\d{3} \d{3}"，"RKJ-YP6" -> "[A-Za-z0-9]{3}\-[A-Za-z0-9]{3}"
+fn formatted_pattern(formatted: &str) -> String {
+    let class = if formatted
+        .chars()
+        .all(|c| c.is_ascii_digit() || !c.is_alphanumeric())
+    {
+        r"\d"
+    } else {
+        "[A-Za-z0-9]"
+    };
+
+    let mut pattern = String::new();
+    let mut run = 0;
+    for c in formatted.chars() {
+        if c.is_alphanumeric() {
+            run += 1;
+            continue;
+        }
+        if run > 0 {
+            pattern.push_str(&format!("{}{{{}}}", class, run));
+            run = 0;
+        }
+        pattern.push_str(&fancy_regex::escape(&c.to_string()));
+    }
+    if run > 0 {
+        pattern.push_str(&format!("{}{{{}}}", class, run));
+    }
+    pattern
+}
+
+// 验证码前紧挨着的非数字文字，如 "验证码为"、"code is"
+fn anchor_before(text: &str) -> Option<String> {
+    let chars: Vec<char> = text
+        .chars()
+        .rev()
+        .take_while(|c| !c.is_ascii_digit() && *c != '\n')
+        .take(MAX_ANCHOR_CHARS)
+        .collect();
+    let mut anchor: String = chars.into_iter().rev().collect();
+
+    // 截断在单词中间时去掉不完整的单词，否则关键词匹配会因为单词边界失败
+    let truncated = text[..text.len() - anchor.len()]
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric);
+    if truncated && anchor.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        anchor = anchor
+            .split_once(char::is_whitespace)
+            .map(|(_, rest)| rest.to_string())
+            .unwrap_or_default();
+    }
+    non_trivial(anchor.trim())
+}
+
+// 验证码后紧挨着的非数字文字，如 "is your code"
+fn anchor_after(text: &str) -> Option<String> {
+    let mut anchor: String = text
+        .chars()
+        .take_while(|c| !c.is_ascii_digit() && *c != '\n')
+        .take(MAX_ANCHOR_CHARS)
+        .collect();
+
+    let truncated = text[anchor.len()..]
+        .chars()
+        .next()
+        .is_some_and(char::is_alphanumeric);
+    if truncated && anchor.ends_with(|c: char| c.is_ascii_alphabetic()) {
+        anchor = anchor
+            .rsplit_once(char::is_whitespace)
+            .map(|(rest, _)| rest.to_string())
+            .unwrap_or_default();
+    }
+    non_trivial(anchor.trim())
+}
+
+// 锚点至少要有两个字母或汉字，只剩标点时不足以定位验证码
+fn non_trivial(anchor: &str) -> Option<String> {
+    let letters = anchor.chars().filter(|c| c.is_alphabetic()).count();
+    (letters >= 2).then(|| anchor.to_string())
+}
+
+/// 在监听进程中记录一次纠正：样本存入纠正目录供重新训练分类模型，
+/// 生成的规则交给 [`SharedExtractor::add_learned_rule`] 写入配置。
+///
+/// 返回生成的规则，无法生成时为 None。
+pub fn apply_correction(
+    extractor: &SharedExtractor,
+    pending: &PendingCorrection,
+    code: &str,
+) -> Result<Option<SenderRule>, Box<dyn std::error::Error>> {
+    corpus::append_case(
+        &classifier::get_corrections_dir(),
+        &CorpusCase {
+            message: pending.message.clone(),
+            sender: pending.sender.clone(),
+            expected: Some(code.to_string()),
+            language: corpus::detect_language(&pending.message).to_string(),
+            notes: Some(format!("corrected from {}", pending.code)),
+            known_failure: false,
+        },
+    )?;
+
+    let Some(candidate) = pending.candidates.iter().find(|c| c.code == code) else {
+        return Ok(None);
+    };
+    let Some(rule) = learn_rule(
+        &pending.message,
+        pending.sender.as_deref(),
+        pending.service.as_deref(),
+        candidate.span,
+    ) else {
+        return Ok(None);
+    };
+
+    extractor.add_learned_rule(rule.clone())?;
+    Ok(Some(rule))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser::ExtractorChain;
+
+    #[test]
+    fn test_learned_rule_extracts_corrected_code() {
+        // 被遮盖的订单号也作为候选码，遮盖过度时可以纠正
+        let message = "【某某银行】订单号 482913 的动态口令 7730 ，5分钟内有效，验证码请勿泄露";
+        let mut config = Config::default();
+        let extractor = ExtractorChain::new(&config);
+        let result = extractor.extract(message, Some("95599")).unwrap();
+        let pending = PendingCorrection::from_result(message, Some("95599"), &result);
+        assert_eq!(result.code, "7730");
+        assert_eq!(
+            pending.candidates,
+            vec![CorrectionCandidate {
+                code: "482913".to_string(),
+                span: span_of(message, "482913"),
+            }]
+        );
+
+        let rule = learn_rule(message, Some("95599"), None, span_of(message, "7730")).unwrap();
+        assert_eq!(rule.keywords, Some(vec!["的动态口令".to_string()]));
+        assert!(rule.learned);
+        config.rules.push(rule);
+        let extractor = ExtractorChain::new(&config);
+
+        let next = "【某某银行】订单号 106622 的动态口令 5129 ，5分钟内有效，验证码请勿泄露";
+        let result = extractor.extract(next, Some("95599")).unwrap();
+        assert_eq!(result.code, "5129");
+        assert!(result.rule.unwrap().starts_with("learned:95599"));
+
+        // 同一发件人换了模板时退回全局配置
+        let other = extractor
+            .extract("您的验证码为 830271，请勿泄露", Some("95599"))
+            .unwrap();
+        assert_eq!(other.code, "830271");
+        assert!(other.rule.is_none());
+
+        // 验证码在开头时以后面的文字为锚点，邮件发件人只保留地址
+        let message = "G-482913 is your Google verification code.";
+        let rule = learn_rule(
+            message,
+            Some("Google <no-reply@accounts.google.com>"),
+            Some("Google"),
+            span_of(message, "G-482913"),
+        )
+        .unwrap();
+        assert_eq!(rule.sender.as_deref(), Some("no-reply@accounts.google.com"));
+        assert_eq!(rule.keywords, Some(vec!["is your".to_string()]));
+
+        assert!(learn_rule("482913", None, Some("Steam"), (0, 6)).is_none());
+        assert!(learn_rule("code 482913", None, None, (5, 11)).is_none());
+    }
+
+    #[test]
+    fn test_learned_rule_anchors_on_selected_span() {
+        // 所选验证码是分组、全角写法，或前面出现过相同的数字时，以所选位置为准
+        let cases = [
+            (
+                "订单 123456 已支付，动态口令 123 456 请勿泄露",
+                "123 456",
+                "123456",
+            ),
+            ("动态口令：１２３４５６，请勿泄露", "１２３４５６", "123456"),
+            (
+                "账户 482913 的登录动态口令 482913 请勿泄露",
+                "482913",
+                "482913",
+            ),
+        ];
+
+        for (message, formatted, expected) in cases {
+            let start = message.rfind(formatted).unwrap();
+            let span = (start, start + formatted.len());
+            let rule = learn_rule(message, Some("95599"), None, span)
+                .unwrap_or_else(|| panic!("should learn a rule from {:?}", message));
+
+            let mut config = Config::default();
+            config.rules.push(rule);
+            let result = ExtractorChain::new(&config)
+                .extract(message, Some("95599"))
+                .unwrap();
+            assert_eq!(result.code, expected, "message: {:?}", message);
+            assert_eq!(result.span, span, "message: {:?}", message);
+        }
+    }
+
+    fn span_of(message: &str, code: &str) -> (usize, usize) {
+        let start = message.find(code).unwrap();
+        (start, start + code.len())
+    }
+}
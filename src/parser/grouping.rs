@@ -0,0 +1,111 @@
+use fancy_regex::Regex;
+use std::sync::LazyLock;
+
+// 由空格或 '-' 分隔的 2~4 位数字组，同一验证码内分隔符一致，例如 "123 456"、"482-913"、"12 34 56"。
+// 最后一组后面紧跟汉字或时间单位时是 "4821 10分钟" 这样的有效期，不与前面的数字合并
+static DIGIT_GROUPS_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?<![A-Za-z0-9-]|\d[ -])\d{2,4}([ -])\d{2,4}(?:\1\d{2,4}){0,2}(?![A-Za-z0-9]|[ -]\d|\p{Han}|\s*(?i:minutes?|mins?|seconds?|secs?|hours?|hrs?|days?)\b)",
+    )
+    .unwrap()
+});
+
+// 单个大写字母加 '-' 的前缀，例如 Google 的 "G-123456"
+static PREFIXED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?<![A-Za-z0-9-])[A-Z]-(\d{4,8})(?![A-Za-z0-9-])").unwrap());
+
+// 合并后验证码的数字位数范围
+const MIN_DIGITS: usize = 4;
+const MAX_DIGITS: usize = 8;
+
+/// 按分组书写的验证码
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupedCode {
+    /// 去掉分隔符和前缀后需要输入的验证码
+    pub canonical: String,
+    /// 整段分组文本的字节区间
+    pub span: (usize, usize),
+}
+
+/// 找出文本中按空格、'-' 分组或带字母前缀的验证码
+pub fn find_grouped_codes(text: &str) -> Vec<GroupedCode> {
+    let mut codes: Vec<GroupedCode> = Vec::new();
+
+    for mat in DIGIT_GROUPS_RE.find_iter(text).flatten() {
+        // 各组位数必须相同，如 3+3、4+4、2+2+2，避免把验证码和后面无关的数字拼在一起
+        let mut sizes = mat.as_str().split([' ', '-']).map(str::len);
+        let first = sizes.next().unwrap_or(0);
+        if !sizes.all(|size| size == first) {
+            continue;
+        }
+
+        let canonical: String = mat.as_str().chars().filter(char::is_ascii_digit).collect();
+        if (MIN_DIGITS..=MAX_DIGITS).contains(&canonical.len()) {
+            codes.push(GroupedCode {
+                canonical,
+                span: (mat.start(), mat.end()),
+            });
+        }
+    }
+
+    for captures in PREFIXED_RE.captures_iter(text).flatten() {
+        let (Some(whole), Some(digits)) = (captures.get(0), captures.get(1)) else {
+            continue;
+        };
+        let span = (whole.start(), whole.end());
+        if codes.iter().any(|c| c.span.0 < span.1 && span.0 < c.span.1) {
+            continue;
+        }
+        codes.push(GroupedCode {
+            canonical: digits.as_str().to_string(),
+            span,
+        });
+    }
+
+    codes.sort_by_key(|code| code.span);
+    codes
+}
+
+/// `text` 整体是一个分组验证码时返回去掉分隔符后的验证码，如 "123 456" -> "123456"
+pub fn canonical_code(text: &str) -> Option<String> {
+    find_grouped_codes(text)
+        .into_iter()
+        .find(|code| code.span == (0, text.len()))
+        .map(|code| code.canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grouped_codes_are_joined() {
+        let cases = vec![
+            ("your code is 123 456", Some("123456")),
+            ("WhatsApp code 482-913", Some("482913")),
+            ("验证码 12 34 56 五分钟内有效", Some("123456")),
+            ("G-482913 is your Google verification code.", Some("482913")),
+            // 分隔符不一致、位数过多或含字母时不视为分组验证码
+            ("code 123 456-789", None),
+            ("code 1234 5678 9012", None),
+            ("code RKJ-YP6", None),
+            // 后面的数字是有效期，或各组位数不同
+            ("验证码 4821 10分钟内有效", None),
+            ("Your code is 4821 30 minutes remain", None),
+            ("code 4821 482", None),
+        ];
+
+        for (input, expected) in cases {
+            let codes = find_grouped_codes(input);
+            assert_eq!(
+                codes.first().map(|c| c.canonical.as_str()),
+                expected,
+                "input: {:?}",
+                input
+            );
+        }
+
+        assert_eq!(canonical_code("123 456").as_deref(), Some("123456"));
+        assert_eq!(canonical_code("code 123 456"), None);
+    }
+}
@@ -0,0 +1,258 @@
+use std::sync::OnceLock;
+
+/// 书写系统，用于根据消息文字自动启用关键词包
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    /// 平假名、片假名
+    Kana,
+    Hangul,
+    Cyrillic,
+    Thai,
+    Arabic,
+    /// 带变音符号的拉丁字母，如 ä、é、ñ、ã、ư
+    LatinExtended,
+}
+
+/// 一种语言的验证码关键词
+pub struct KeywordPack {
+    /// 语言代码，与系统语言的前缀比较，如 "ja"、"de"
+    pub language: &'static str,
+    pub script: Script,
+    pub keywords: &'static [&'static str],
+    /// 含义宽泛的弱关键词，如 "コード"、"código"
+    pub weak: &'static [&'static str],
+}
+
+pub const PACKS: &[KeywordPack] = &[
+    KeywordPack {
+        language: "ja",
+        script: Script::Kana,
+        keywords: &[
+            "認証コード",
+            "確認コード",
+            "認証番号",
+            "確認番号",
+            "セキュリティコード",
+            "ワンタイムパスワード",
+        ],
+        weak: &["コード"],
+    },
+    KeywordPack {
+        language: "ko",
+        script: Script::Hangul,
+        keywords: &[
+            "인증번호",
+            "인증 번호",
+            "인증코드",
+            "확인코드",
+            "확인 코드",
+            "보안코드",
+        ],
+        weak: &["코드"],
+    },
+    KeywordPack {
+        language: "de",
+        script: Script::LatinExtended,
+        keywords: &[
+            "Bestätigungscode",
+            "Verifizierungscode",
+            "Sicherheitscode",
+            "Einmalcode",
+            "Anmeldecode",
+        ],
+        weak: &[],
+    },
+    KeywordPack {
+        language: "fr",
+        script: Script::LatinExtended,
+        keywords: &[
+            "code de vérification",
+            "code de confirmation",
+            "code de sécurité",
+            "code à usage unique",
+        ],
+        weak: &[],
+    },
+    KeywordPack {
+        language: "es",
+        script: Script::LatinExtended,
+        keywords: &[
+            "código de verificación",
+            "código de seguridad",
+            "código de confirmación",
+            "código de acceso",
+        ],
+        weak: &["código"],
+    },
+    KeywordPack {
+        language: "pt",
+        script: Script::LatinExtended,
+        keywords: &[
+            "código de verificação",
+            "código de segurança",
+            "código de confirmação",
+            "código de acesso",
+        ],
+        weak: &["código"],
+    },
+    KeywordPack {
+        language: "ru",
+        script: Script::Cyrillic,
+        keywords: &[
+            "код подтверждения",
+            "проверочный код",
+            "код безопасности",
+            "одноразовый код",
+            "код для входа",
+        ],
+        weak: &["код"],
+    },
+    KeywordPack {
+        language: "vi",
+        script: Script::LatinExtended,
+        keywords: &[
+            "mã xác nhận",
+            "mã xác minh",
+            "mã xác thực",
+            "mã bảo mật",
+            "mã OTP",
+        ],
+        weak: &["mã"],
+    },
+    KeywordPack {
+        language: "th",
+        script: Script::Thai,
+        keywords: &["รหัสยืนยัน", "รหัส OTP", "รหัสผ่านครั้งเดียว"],
+        weak: &["รหัส"],
+    },
+    KeywordPack {
+        language: "ar",
+        script: Script::Arabic,
+        keywords: &["رمز التحقق", "رمز التأكيد", "كود التحقق", "رمز الأمان"],
+        weak: &["رمز"],
+    },
+];
+
+static SYSTEM_LANGUAGE: OnceLock<String> = OnceLock::new();
+
+/// 记录系统语言（如 "ja-JP"），之后构建的提取器会自动启用对应的关键词包
+pub fn set_system_language(locale: &str) {
+    let _ = SYSTEM_LANGUAGE.set(locale.to_string());
+}
+
+pub fn system_language() -> Option<&'static str> {
+    SYSTEM_LANGUAGE.get().map(String::as_str)
+}
+
+/// 语言代码对应的关键词包，"ja-JP"、"pt_BR" 这样的写法只比较语言部分
+pub fn for_language(locale: &str) -> Option<&'static KeywordPack> {
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    PACKS
+        .iter()
+        .find(|pack| pack.language.eq_ignore_ascii_case(language))
+}
+
+/// 同一书写系统下的所有关键词包
+pub fn for_script(script: Script) -> impl Iterator<Item = &'static KeywordPack> {
+    PACKS.iter().filter(move |pack| pack.script == script)
+}
+
+/// 把关键词包合并到配置中的关键词和弱关键词之后，返回新的列表。
+///
+/// 关键词包的弱关键词同时加入两个列表，配置中只列在 `weak` 里的关键词仍然不会生效。
+pub fn merge<'a>(
+    keywords: &[String],
+    weak: &[String],
+    packs: impl IntoIterator<Item = &'a KeywordPack>,
+) -> (Vec<String>, Vec<String>) {
+    let mut keywords = keywords.to_vec();
+    let mut weak = weak.to_vec();
+    let push_new = |list: &mut Vec<String>, items: &[&str]| {
+        for item in items {
+            if !list.iter().any(|k| k == item) {
+                list.push(item.to_string());
+            }
+        }
+    };
+    for pack in packs {
+        push_new(&mut keywords, pack.keywords);
+        push_new(&mut keywords, pack.weak);
+        push_new(&mut weak, pack.weak);
+    }
+    (keywords, weak)
+}
+
+/// 找出消息中出现最多的非中英文书写系统
+pub fn detect_script(text: &str) -> Option<Script> {
+    let mut counts = [0usize; 6];
+    for c in text.chars() {
+        let script = match c {
+            '\u{3040}'..='\u{30ff}' => Script::Kana,
+            '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' | '\u{ac00}'..='\u{d7af}' => {
+                Script::Hangul
+            }
+            '\u{0400}'..='\u{04ff}' => Script::Cyrillic,
+            '\u{0e00}'..='\u{0e7f}' => Script::Thai,
+            '\u{0600}'..='\u{06ff}' | '\u{0750}'..='\u{077f}' => Script::Arabic,
+            '\u{00c0}'..='\u{024f}' | '\u{1e00}'..='\u{1eff}' if c != '×' && c != '÷' => {
+                Script::LatinExtended
+            }
+            _ => continue,
+        };
+        counts[script as usize] += 1;
+    }
+
+    [
+        Script::Kana,
+        Script::Hangul,
+        Script::Cyrillic,
+        Script::Thai,
+        Script::Arabic,
+        Script::LatinExtended,
+    ]
+    .into_iter()
+    .filter(|script| counts[*script as usize] > 0)
+    .max_by_key(|script| counts[*script as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packs_from_locale_and_script() {
+        assert_eq!(for_language("ja-JP").map(|p| p.language), Some("ja"));
+        assert_eq!(for_language("pt_BR").map(|p| p.language), Some("pt"));
+        assert!(for_language("zh-CN").is_none());
+
+        let cases = vec![
+            ("認証コード：482913", Some(Script::Kana)),
+            ("인증번호 [482913]", Some(Script::Hangul)),
+            ("Ваш код: 482913", Some(Script::Cyrillic)),
+            ("รหัส OTP 482913", Some(Script::Thai)),
+            ("رمز التحقق 482913", Some(Script::Arabic)),
+            ("Ihr Bestätigungscode: 482913", Some(Script::LatinExtended)),
+            ("您的验证码为 482913", None),
+            ("Your code is 482913", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(detect_script(text), expected, "text: {}", text);
+        }
+
+        let latin: Vec<&str> = for_script(Script::LatinExtended)
+            .map(|p| p.language)
+            .collect();
+        assert_eq!(latin, vec!["de", "fr", "es", "pt", "vi"]);
+
+        // 关键词包的弱关键词加入关键词列表，配置中只在 weak 里的 "code" 不会被加入
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (keywords, weak) = merge(
+            &strings(&["验证码"]),
+            &strings(&["code"]),
+            for_language("ja"),
+        );
+        assert!(keywords.iter().any(|k| k == "コード"));
+        assert!(!keywords.iter().any(|k| k == "code"));
+        assert!(weak.iter().any(|k| k == "コード"));
+    }
+}
@@ -0,0 +1,182 @@
+use super::normalize::MappedText;
+
+// 原始关键词及其小写形式
+struct Keyword {
+    text: String,
+    lower: String,
+    strong: bool,
+}
+
+impl Keyword {
+    fn new(text: &str, strong: bool) -> Self {
+        Self {
+            text: text.to_string(),
+            lower: text.to_lowercase(),
+            strong,
+        }
+    }
+}
+
+/// 关键词在文本中的一次出现
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeywordMatch {
+    pub keyword: String,
+    /// 在文本中的字节区间
+    pub span: (usize, usize),
+    /// 是否为强关键词，如 "验证码"；弱关键词如 "code"
+    pub strong: bool,
+}
+
+/// 分层的关键词集合：强关键词、弱关键词和排除词
+pub struct KeywordSet {
+    keywords: Vec<Keyword>,
+    negatives: Vec<Keyword>,
+}
+
+impl KeywordSet {
+    /// 同时出现在 `keywords` 和 `weak` 中的关键词按弱关键词处理，只在 `weak` 中的不算关键词
+    pub fn new(keywords: &[String], weak: &[String], negative: &[String]) -> Self {
+        let is_weak = |keyword: &String| weak.iter().any(|w| w.eq_ignore_ascii_case(keyword));
+        let compiled: Vec<Keyword> = keywords
+            .iter()
+            .filter(|keyword| !keyword.is_empty())
+            .map(|keyword| Keyword::new(keyword, !is_weak(keyword)))
+            .collect();
+
+        Self {
+            keywords: compiled,
+            negatives: negative
+                .iter()
+                .filter(|keyword| !keyword.is_empty())
+                .map(|keyword| Keyword::new(keyword, false))
+                .collect(),
+        }
+    }
+
+    /// 找出所有关键词出现的位置，以及所有排除词出现的位置。
+    ///
+    /// 落在排除词（如 "promo code"）内的关键词会被丢弃，被更长关键词包含的出现也只保留较长者。
+    pub fn find(&self, text: &str) -> (Vec<KeywordMatch>, Vec<(usize, usize)>) {
+        // 转小写可能改变字节长度，所以要把小写文本中的位置映射回 text
+        let text_lower = MappedText::lowercase(text);
+
+        let negatives: Vec<(usize, usize)> = self
+            .negatives
+            .iter()
+            .flat_map(|keyword| find_word(&text_lower, &keyword.lower))
+            .collect();
+
+        let mut matches: Vec<KeywordMatch> = Vec::new();
+        for keyword in &self.keywords {
+            for span in find_word(&text_lower, &keyword.lower) {
+                if negatives.iter().any(|n| overlaps(*n, span)) {
+                    continue;
+                }
+                matches.push(KeywordMatch {
+                    keyword: keyword.text.clone(),
+                    span,
+                    strong: keyword.strong,
+                });
+            }
+        }
+
+        // 示例："verification code" 中的 "code" 只是较长关键词的一部分
+        let contained = |m: &KeywordMatch, others: &[KeywordMatch]| {
+            others
+                .iter()
+                .any(|o| o.span != m.span && o.span.0 <= m.span.0 && m.span.1 <= o.span.1)
+        };
+        let mut result: Vec<KeywordMatch> = matches
+            .iter()
+            .filter(|m| !contained(m, &matches))
+            .cloned()
+            .collect();
+        result.sort_by_key(|m| m.span);
+        result.dedup_by(|a, b| a.span == b.span);
+
+        (result, negatives)
+    }
+}
+
+// 在小写文本中查找关键词的全部出现，返回原文中的区间；以单词字符开头/结尾的关键词需要完整单词匹配
+pub(super) fn find_word(text_lower: &MappedText, keyword: &str) -> Vec<(usize, usize)> {
+    let text = text_lower.text.as_str();
+    let check_start = keyword.starts_with(is_word_char);
+    let check_end = keyword.ends_with(is_word_char);
+
+    text.match_indices(keyword)
+        .filter(|(pos, _)| {
+            let end = pos + keyword.len();
+            let before_ok =
+                !check_start || !text[..*pos].chars().next_back().is_some_and(is_word_char);
+            let after_ok = !check_end || !text[end..].chars().next().is_some_and(is_word_char);
+            before_ok && after_ok
+        })
+        .map(|(pos, _)| text_lower.original_span((pos, pos + keyword.len())))
+        .collect()
+}
+
+// 用空格分词的文字中的字母和数字，如英文、德文、俄文、阿拉伯文；
+// 中日韩文和泰文不用空格分词（韩文的助词直接连在词后），不做单词边界检查
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric()
+        && !matches!(c,
+            '\u{0e00}'..='\u{0e7f}'
+            | '\u{1100}'..='\u{11ff}'
+            | '\u{2e80}'..='\u{9fff}'
+            | '\u{ac00}'..='\u{d7af}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{ff00}'..='\u{ffef}')
+}
+
+fn overlaps(a: (usize, usize), b: (usize, usize)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyword_set() -> KeywordSet {
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        KeywordSet::new(
+            &strings(&["验证码", "verification code", "code"]),
+            &strings(&["code"]),
+            &strings(&["promo code", "优惠码"]),
+        )
+    }
+
+    #[test]
+    fn test_word_boundary_and_negative_keywords() {
+        let set = keyword_set();
+
+        let (matches, _) = set.find("Failed to decode the message, zipcode 10001");
+        assert!(matches.is_empty());
+
+        let (matches, negatives) = set.find("Use promo code SAVE20 at checkout");
+        assert!(matches.is_empty());
+        assert_eq!(negatives.len(), 1);
+
+        let text = "Promo code SAVE20. Your verification code is 123456, code expires soon";
+        let (matches, _) = set.find(text);
+        let found: Vec<(&str, bool)> = matches
+            .iter()
+            .map(|m| (&text[m.span.0..m.span.1], m.strong))
+            .collect();
+        assert_eq!(found, vec![("verification code", true), ("code", false)]);
+    }
+
+    #[test]
+    fn test_weak_only_entries_are_not_keywords() {
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        // 从 verification_keywords 中删掉 "code" 后，默认弱关键词中的 "code" 也不再生效
+        let set = KeywordSet::new(&strings(&["验证码"]), &strings(&["code", "验证"]), &[]);
+
+        let (matches, _) = set.find("your code is 482913");
+        assert!(matches.is_empty());
+
+        let (matches, _) = set.find("您的验证码为 482913");
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].strong);
+    }
+}
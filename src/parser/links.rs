@@ -0,0 +1,146 @@
+use super::keywords::find_word;
+use super::mask::URL_RE;
+use super::normalize::{MappedText, char_distance};
+
+// 提示这是登录或确认链接的词
+const SIGN_IN_KEYWORDS: &[&str] = &[
+    "sign in",
+    "sign-in",
+    "log in",
+    "login",
+    "magic link",
+    "confirm your email",
+    "verify your email",
+    "confirm your account",
+    "登录",
+    "登入",
+    "确认邮箱",
+    "验证邮箱",
+];
+
+// 链接与关键词之间允许的最大距离（字符数），邮件中按钮文字和链接通常隔着一段说明
+const MAX_LINK_DISTANCE: usize = 200;
+
+// 链接末尾常见的、不属于链接本身的标点
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', '\'', '"'];
+
+/// 邮件或短信中的一键登录链接
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignInLink {
+    pub url: String,
+    /// 链接在原文中的字节区间
+    pub span: (usize, usize),
+    /// 链接附近的登录关键词
+    pub keyword: String,
+}
+
+/// 查找离登录关键词最近、且域名在白名单内的 https 链接。
+///
+/// 不在白名单内的链接一律忽略，营销邮件中的任意链接不会被当成登录链接。
+pub fn find_sign_in_link(text: &str, allowed_domains: &[String]) -> Option<SignInLink> {
+    let text_lower = MappedText::lowercase(text);
+    let keywords: Vec<(&str, (usize, usize))> = SIGN_IN_KEYWORDS
+        .iter()
+        .flat_map(|keyword| {
+            find_word(&text_lower, keyword)
+                .into_iter()
+                .map(move |span| (*keyword, span))
+        })
+        .collect();
+    if keywords.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<(usize, SignInLink)> = None;
+    for mat in URL_RE.find_iter(text).flatten() {
+        let url = mat.as_str().trim_end_matches(TRAILING_PUNCTUATION);
+        let span = (mat.start(), mat.start() + url.len());
+
+        let Some(host) = https_host(url) else {
+            continue;
+        };
+        if !is_allowed(&host, allowed_domains) {
+            log::debug!("Ignoring link outside the sign-in allowlist: {}", host);
+            continue;
+        }
+
+        let Some((distance, keyword)) = keywords
+            .iter()
+            .map(|(keyword, keyword_span)| (char_distance(text, span, *keyword_span), *keyword))
+            .min_by_key(|(distance, _)| *distance)
+        else {
+            continue;
+        };
+        if distance <= MAX_LINK_DISTANCE && best.as_ref().is_none_or(|(d, _)| distance < *d) {
+            best = Some((
+                distance,
+                SignInLink {
+                    url: url.to_string(),
+                    span,
+                    keyword: keyword.to_string(),
+                },
+            ));
+        }
+    }
+
+    best.map(|(_, link)| link)
+}
+
+// 只接受 https 链接，返回小写的主机名
+fn https_host(url: &str) -> Option<String> {
+    let rest = url
+        .get(..8)?
+        .eq_ignore_ascii_case("https://")
+        .then(|| &url[8..])?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    // "https://slack.com@evil.example" 的真实主机是 evil.example
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+fn is_allowed(host: &str, allowed_domains: &[String]) -> bool {
+    allowed_domains.iter().any(|domain| {
+        let domain = domain.trim_start_matches('.').to_lowercase();
+        host == domain || host.ends_with(&format!(".{}", domain))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_in_link_requires_keyword_and_allowlisted_domain() {
+        let allowed = vec!["slack.com".to_string(), "notion.so".to_string()];
+
+        let text = "Click the button below to sign in to Slack: https://acme.slack.com/z-app-123/confirm?token=abc.";
+        let link = find_sign_in_link(text, &allowed).expect("should find link");
+        assert_eq!(
+            link.url,
+            "https://acme.slack.com/z-app-123/confirm?token=abc"
+        );
+        assert_eq!(&text[link.span.0..link.span.1], link.url);
+        assert_eq!(link.keyword, "sign in");
+
+        // 不在白名单、伪装成白名单域名或不是 https 的链接都不算
+        let cases = [
+            "Log in now: https://example.com/login?token=abc",
+            "Log in now: https://slack.com@evil.example/login",
+            "Log in now: https://slack.com.evil.example/login",
+            "Log in now: http://acme.slack.com/login",
+            "Check out our new templates https://www.notion.so/templates",
+        ];
+        for text in cases {
+            assert!(
+                find_sign_in_link(text, &allowed).is_none(),
+                "text: {}",
+                text
+            );
+        }
+    }
+}
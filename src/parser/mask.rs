@@ -0,0 +1,176 @@
+use crate::config::MaskingOptions;
+use fancy_regex::Regex;
+use std::sync::LazyLock;
+
+// 所有正则只在首次使用时编译一次
+//
+// 不带协议的域名只从一串域名字符的开头尝试匹配，"1-1-1-…" 这样的长串不会在每个位置都回溯一遍
+pub(super) static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:https?://|www\.)[a-z0-9\-._~:/?#\[\]@!$&'()*+,;=%]+|(?<![a-z0-9.-])(?:[a-z0-9-]+\.)+[a-z]{2,}/[a-z0-9\-._~:/?#\[\]@!$&'()*+,;=%]*",
+    )
+    .unwrap()
+});
+
+static EMAIL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap());
+
+// 验证码不会出现在这么长的字母数字串中，提前遮盖，后面的正则不必在长串的每个位置上回溯
+static LONG_TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[A-Za-z0-9-]{65,}").unwrap());
+
+static DATE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?<!\d)(?:\d{4}[-/.年]\d{1,2}[-/.月]\d{1,2}日?|\d{1,2}月\d{1,2}日)").unwrap()
+});
+
+static TIME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?<!\d)\d{1,2}(?::\d{2}){1,2}(?:\.\d+)?(?!\d)").unwrap());
+
+static PHONE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        // 中国大陆手机号，可带 +86
+        r"(?<![\d+])(?:\+?86[- ]?)?1[3-9]\d{9}(?!\d)",
+        // 400/800 客服电话
+        r"|(?<!\d)[48]00[- ]?\d{3}[- ]?\d{4}(?!\d)",
+        // 带区号的固定电话
+        r"|(?<!\d)0\d{2,3}-\d{7,8}(?!\d)",
+        // 北美格式
+        r"|\(\d{3}\)\s?\d{3}-\d{4}|(?<!\d)\d{3}-\d{3}-\d{4}(?!\d)",
+        // 其他国际号码
+        r"|\+\d{1,3}[- ]?\(?\d{1,4}\)?(?:[- ]?\d{2,4}){2,4}(?!\d)",
+    ))
+    .unwrap()
+});
+
+static AMOUNT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:[¥￥$€£]|\b(?:rmb|usd|cny|eur)\b)\s?\d+(?:[.,]\d+)*|\d+(?:[.,]\d+)*\s?(?:元|块|美元|\b(?:rmb|usd|cny|eur)\b)",
+    )
+    .unwrap()
+});
+
+// 只遮盖第一个捕获组，保留前面的"订单号"等字样
+static ORDER_NUMBER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:订单号|订单编号|运单号|快递单号|物流单号|\border\s*(?:no\.?|number|#|id)|\btracking\s*(?:no\.?|number|#|id))\s*[:：#]?\s*([a-z0-9-]{6,})",
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Url,
+    Email,
+    Date,
+    Time,
+    PhoneNumber,
+    Amount,
+    OrderNumber,
+    LongToken,
+}
+
+/// 被遮盖的实体及其在原文中的字节区间
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaskedEntity {
+    pub kind: EntityKind,
+    pub span: (usize, usize),
+}
+
+/// 将识别出的实体替换为等长的空格，返回遮盖后的文本和实体列表。
+///
+/// 遮盖后的文本与原文字节长度一致，所以其中的位置可以直接用于原文。
+pub fn mask_entities(text: &str, options: &MaskingOptions) -> (String, Vec<MaskedEntity>) {
+    let maskers: [(bool, EntityKind, &Regex); 8] = [
+        // URL 和邮箱优先，避免其中的数字被当成电话或日期
+        (options.urls, EntityKind::Url, &URL_RE),
+        (options.emails, EntityKind::Email, &EMAIL_RE),
+        (options.long_tokens, EntityKind::LongToken, &LONG_TOKEN_RE),
+        (options.dates, EntityKind::Date, &DATE_RE),
+        (options.times, EntityKind::Time, &TIME_RE),
+        (options.phone_numbers, EntityKind::PhoneNumber, &PHONE_RE),
+        (options.amounts, EntityKind::Amount, &AMOUNT_RE),
+        (
+            options.order_numbers,
+            EntityKind::OrderNumber,
+            &ORDER_NUMBER_RE,
+        ),
+    ];
+
+    let mut masked = text.to_string();
+    let mut entities = Vec::new();
+
+    for (enabled, kind, re) in maskers {
+        if !enabled {
+            continue;
+        }
+
+        let mut spans = Vec::new();
+        for captures in re.captures_iter(&masked).flatten() {
+            // 有捕获组时只遮盖捕获组，否则遮盖整个匹配
+            if let Some(mat) = captures.get(1).or_else(|| captures.get(0)) {
+                spans.push((mat.start(), mat.end()));
+            }
+        }
+
+        for span in spans {
+            masked.replace_range(span.0..span.1, &" ".repeat(span.1 - span.0));
+            entities.push(MaskedEntity { kind, span });
+        }
+    }
+
+    entities.sort_by_key(|entity| entity.span);
+    (masked, entities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masking_preserves_offsets() {
+        let text = "您在2024-04-02 17:23:35登录，验证码：524678，客服4001001111";
+        let (masked, entities) = mask_entities(text, &MaskingOptions::default());
+
+        assert_eq!(masked.len(), text.len());
+        assert_eq!(masked.find("524678"), text.find("524678"));
+        assert!(!masked.contains("2024"));
+        assert!(!masked.contains("17:23"));
+        assert!(!masked.contains("4001001111"));
+
+        let kinds: Vec<EntityKind> = entities.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![EntityKind::Date, EntityKind::Time, EntityKind::PhoneNumber]
+        );
+    }
+
+    #[test]
+    fn test_disabled_masker_keeps_entity() {
+        let options = MaskingOptions {
+            phone_numbers: false,
+            ..MaskingOptions::default()
+        };
+        let (masked, entities) = mask_entities("客服电话 4001001111", &options);
+
+        assert!(masked.contains("4001001111"));
+        assert!(entities.is_empty());
+    }
+
+    #[test]
+    fn test_long_tokens_are_masked() {
+        let token = "a1b2".repeat(20);
+        let text = format!("token={} 验证码 482913", token);
+        let (masked, entities) = mask_entities(&text, &MaskingOptions::default());
+
+        assert!(!masked.contains(&token));
+        assert!(masked.contains("482913"));
+        assert_eq!(entities[0].kind, EntityKind::LongToken);
+
+        let options = MaskingOptions {
+            long_tokens: false,
+            ..MaskingOptions::default()
+        };
+        let (masked, entities) = mask_entities(&text, &options);
+        assert!(masked.contains(&token));
+        assert!(entities.is_empty());
+    }
+}
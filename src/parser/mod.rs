@@ -0,0 +1,757 @@
+mod category;
+mod chain;
+pub mod classifier;
+pub mod corpus;
+pub mod corrections;
+mod grouping;
+pub mod keyword_packs;
+mod keywords;
+mod links;
+mod mask;
+mod normalize;
+mod otp_model;
+mod roles;
+mod rules;
+mod scoring;
+mod service;
+mod shared;
+pub mod strategies;
+mod validity;
+
+use crate::config::SenderRule;
+pub use category::MessageCategory;
+pub use chain::{CodeExtractor, Extraction, ExtractorChain, Message};
+use fancy_regex::Regex;
+use keywords::KeywordMatch;
+pub use links::SignInLink;
+pub use mask::{EntityKind, MaskedEntity};
+pub use roles::CodeRole;
+use scoring::{CandidateContext, CandidateScorer};
+pub use shared::SharedExtractor;
+use std::time::Duration;
+
+// 候选码与关键词之间允许的最大距离（字符数）
+const MAX_KEYWORD_DISTANCE: usize = 100;
+
+/// 一次提取的完整结果，包含选中的验证码以及选择它的依据
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractionResult {
+    /// 需要输入的验证码：全角字符已转为半角，分组验证码已去掉分隔符。
+    /// 开启 `preserve_code_grouping` 时与 `formatted` 相同
+    pub code: String,
+    /// 验证码在消息中的书写形式（归一化后），如 "123 456"、"G-482913"
+    pub formatted: String,
+    /// 验证码在原文中的字节区间 (start, end)
+    pub span: (usize, usize),
+    /// 作为锚点的关键词
+    pub keyword: String,
+    /// 验证码与关键词之间相隔的字符数
+    pub distance: usize,
+    /// 评分模型给出的分数
+    pub score: f64,
+    /// 所有被淘汰的候选码及淘汰原因
+    pub rejected: Vec<RejectedCandidate>,
+    /// 提取前被遮盖的日期、电话、链接等实体
+    pub masked: Vec<MaskedEntity>,
+    /// 命中的发件人规则名称，未命中时走全局配置
+    pub rule: Option<String>,
+    /// 给出该验证码的提取策略，按运行顺序排列，见 [`ExtractorChain`]
+    pub strategies: Vec<String>,
+    /// 发送验证码的服务，如 "自如网"、"Slack"、"google.com"
+    pub service: Option<String>,
+    /// 消息类别，取件码等非验证码类别不应自动输入
+    pub category: MessageCategory,
+    /// 置信度，范围 0.0 ~ 1.0
+    pub confidence: f32,
+    /// 验证码的用途，根据前面最近的标签词判断
+    pub role: CodeRole,
+    /// 同一消息中其他可能的验证码，按分数从高到低排列
+    pub alternates: Vec<LabeledCode>,
+    /// 消息中声明的有效期，如 "5分钟内有效"，未声明时为 None
+    pub validity: Option<Duration>,
+}
+
+/// 带用途标签的验证码，用于 [`ExtractorChain::extract_all`] 和备选列表
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledCode {
+    pub code: String,
+    pub formatted: String,
+    pub span: (usize, usize),
+    pub role: CodeRole,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedCandidate {
+    pub code: String,
+    pub span: (usize, usize),
+    pub reason: RejectReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// 不包含任何数字
+    NoDigit,
+    /// 包含多个 '-'
+    TooManyHyphens,
+    /// 长度不在发件人规则要求的范围内
+    LengthOutOfRange,
+    /// 距离关键词超过上限
+    TooFar,
+    /// 评分低于选中的候选码
+    LowerScore,
+    /// 靠近 "promo code" 等排除词，扣分后低于 0
+    NearNegativeKeyword,
+    /// 是分组验证码的一部分，已合并为完整的候选码
+    PartOfGroup,
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            RejectReason::NoDigit => "no digit",
+            RejectReason::TooManyHyphens => "too many hyphens",
+            RejectReason::LengthOutOfRange => "length out of rule range",
+            RejectReason::TooFar => "too far from keyword",
+            RejectReason::LowerScore => "lower score than selected code",
+            RejectReason::NearNegativeKeyword => "near a negative keyword",
+            RejectReason::PartOfGroup => "part of a grouped code",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+fn score_confidence(score: f64, runner_up_score: Option<f64>) -> f32 {
+    // 分数经过 sigmoid 映射，再按与第二名的差距打折：差距为 0 时折半
+    let base = 1.0 / (1.0 + (-score).exp());
+    let margin_factor = match runner_up_score {
+        Some(runner_up) => 1.0 - 0.5 * (-(score - runner_up).max(0.0)).exp(),
+        None => 1.0,
+    };
+    (base * margin_factor) as f32
+}
+
+fn extract_candidate_codes(
+    text: &str,
+    re: &Regex,
+    group: usize,
+    rejected: &mut Vec<RejectedCandidate>,
+) -> Result<Vec<Candidate>, Box<fancy_regex::Error>> {
+    // 使用正则提取候选验证码及其位置，group 为 0 时取整个匹配
+    let mut candidates = Vec::new();
+
+    for result in re.captures_iter(text) {
+        if let Some(mat) = result.map_err(Box::new)?.get(group) {
+            let code = mat.as_str();
+            let span = (mat.start(), mat.end());
+            // 确保提取的字符串中至少包含一个数字
+            if code.chars().any(|c| c.is_ascii_digit()) {
+                candidates.push(Candidate {
+                    code: code.to_string(),
+                    span,
+                });
+            } else {
+                rejected.push(RejectedCandidate {
+                    code: code.to_string(),
+                    span,
+                    reason: RejectReason::NoDigit,
+                });
+            }
+        }
+    }
+
+    Ok(candidates)
+}
+
+fn merge_grouped_codes(
+    text: &str,
+    candidates: Vec<Candidate>,
+    rejected: &mut Vec<RejectedCandidate>,
+) -> Vec<Candidate> {
+    let grouped = grouping::find_grouped_codes(text);
+    if grouped.is_empty() {
+        return candidates;
+    }
+
+    // 与分组区间重叠的正则候选只是分组的片段，由合并后的候选码代替
+    let mut merged = Vec::new();
+    for candidate in candidates {
+        let overlaps = grouped
+            .iter()
+            .any(|g| g.span.0 < candidate.span.1 && candidate.span.0 < g.span.1);
+        if overlaps {
+            rejected.push(RejectedCandidate {
+                code: candidate.code,
+                span: candidate.span,
+                reason: RejectReason::PartOfGroup,
+            });
+        } else {
+            merged.push(candidate);
+        }
+    }
+    merged.extend(grouped.into_iter().map(|g| Candidate {
+        code: g.canonical,
+        span: g.span,
+    }));
+
+    // 保持按出现顺序排列，评分相同时仍取先出现者
+    merged.sort_by_key(|candidate| candidate.span);
+    merged
+}
+
+fn filter_candidates_step1(
+    candidates: Vec<Candidate>,
+    rule: Option<&SenderRule>,
+    category: MessageCategory,
+    rejected: &mut Vec<RejectedCandidate>,
+) -> Vec<Candidate> {
+    // 过滤1: 去除包含多个'-'的候选码，以及不符合规则长度的候选码
+    // 按空格、'-' 分组的验证码已在 merge_grouped_codes 中合并
+    // 取件码常写作 "3-2-1045"（货架-层-编号），允许多个'-'
+    let min_length = rule.and_then(|r| r.min_length).unwrap_or(0);
+    let max_length = rule.and_then(|r| r.max_length).unwrap_or(usize::MAX);
+    let mut filtered = Vec::new();
+
+    for candidate in candidates {
+        let length = candidate.code.chars().count();
+        if length < min_length || length > max_length {
+            rejected.push(RejectedCandidate {
+                code: candidate.code,
+                span: candidate.span,
+                reason: RejectReason::LengthOutOfRange,
+            });
+            continue;
+        }
+
+        // 检查'-'的数量
+        if category != MessageCategory::PickupCode && candidate.code.matches('-').count() > 1 {
+            rejected.push(RejectedCandidate {
+                code: candidate.code,
+                span: candidate.span,
+                reason: RejectReason::TooManyHyphens,
+            });
+            continue;
+        }
+
+        filtered.push(candidate);
+    }
+
+    filtered
+}
+
+// 候选码及其在文本中的字节区间；分组验证码的 code 已去掉分隔符，区间仍覆盖整段分组
+#[derive(Clone)]
+struct Candidate {
+    code: String,
+    span: (usize, usize),
+}
+
+struct ScoredCandidate {
+    code: String,
+    span: (usize, usize),
+    keyword: String,
+    distance: usize,
+    score: f64,
+    runner_up_score: Option<f64>,
+    // 分数大于 0（sigmoid 后置信度过半）的落选候选码，按分数从高到低排列
+    alternates: Vec<(Candidate, f64)>,
+}
+
+fn select_best_candidate(
+    text: &str,
+    candidates: Vec<Candidate>,
+    keyword_matches: &[KeywordMatch],
+    negative_spans: &[(usize, usize)],
+    scorer: &dyn CandidateScorer,
+    rejected: &mut Vec<RejectedCandidate>,
+) -> Option<ScoredCandidate> {
+    // 每个候选码以距离上限内得分最高的关键词出现为锚点，再选出分数最高的候选码
+    let mut best: Option<ScoredCandidate> = None;
+    let mut losers: Vec<(Candidate, f64)> = Vec::new();
+
+    for candidate in candidates {
+        let mut anchored: Option<(f64, usize, &KeywordMatch, bool)> = None;
+        for keyword_match in keyword_matches {
+            let context = CandidateContext {
+                text,
+                code: &candidate.code,
+                span: candidate.span,
+                keyword_span: keyword_match.span,
+                strong_keyword: keyword_match.strong,
+                negative_spans,
+            };
+            let distance = context.distance();
+            if distance > MAX_KEYWORD_DISTANCE {
+                continue;
+            }
+            let score = scorer.score(&context);
+            // 分数相同时保留先出现的关键词
+            if anchored.is_none_or(|(best_score, ..)| score > best_score) {
+                anchored = Some((
+                    score,
+                    distance,
+                    keyword_match,
+                    context.is_near_negative_keyword(),
+                ));
+            }
+        }
+
+        let Some((score, distance, keyword_match, near_negative)) = anchored else {
+            rejected.push(RejectedCandidate {
+                code: candidate.code,
+                span: candidate.span,
+                reason: RejectReason::TooFar,
+            });
+            continue;
+        };
+
+        if near_negative && score < 0.0 {
+            rejected.push(RejectedCandidate {
+                code: candidate.code,
+                span: candidate.span,
+                reason: RejectReason::NearNegativeKeyword,
+            });
+            continue;
+        }
+
+        // 分数相同时保留先出现的候选码
+        let is_better = best.as_ref().is_none_or(|b| score > b.score);
+        if is_better {
+            if let Some(previous) = best.replace(ScoredCandidate {
+                code: candidate.code,
+                span: candidate.span,
+                keyword: keyword_match.keyword.clone(),
+                distance,
+                score,
+                runner_up_score: None,
+                alternates: Vec::new(),
+            }) {
+                let score = previous.score;
+                let candidate = Candidate {
+                    code: previous.code,
+                    span: previous.span,
+                };
+                losers.push((candidate, score));
+            }
+        } else {
+            losers.push((candidate, score));
+        }
+    }
+
+    let mut best = best?;
+    best.runner_up_score = losers
+        .iter()
+        .map(|(_, score)| *score)
+        .max_by(|a, b| a.total_cmp(b));
+
+    best.alternates = losers
+        .iter()
+        .filter(|(_, score)| *score > 0.0)
+        .cloned()
+        .collect();
+    best.alternates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    for (candidate, _) in losers {
+        rejected.push(RejectedCandidate {
+            code: candidate.code,
+            span: candidate.span,
+            reason: RejectReason::LowerScore,
+        });
+    }
+
+    Some(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use proptest::prelude::*;
+    use std::time::Instant;
+
+    fn extract_verification_code(content: &str) -> Option<ExtractionResult> {
+        ExtractorChain::new(&Config::default()).extract(content, None)
+    }
+
+    #[test]
+    fn test_extraction_result_reports_rejected_candidates() {
+        let input = "【倒三角】您的账号W8406772本次登录验证码为666684，请勿泄露";
+        let result = extract_verification_code(input).expect("should extract a code");
+
+        assert_eq!(result.code, "666684");
+        assert_eq!(result.keyword, "验证码");
+        assert_eq!(&input[result.span.0..result.span.1], "666684");
+        assert_eq!(result.distance, 1);
+        assert!(result.confidence > 0.8 && result.confidence <= 1.0);
+        let account_id = result
+            .rejected
+            .iter()
+            .find(|c| c.code == "W8406772")
+            .expect("account id should be rejected");
+        assert_eq!(account_id.reason, RejectReason::LowerScore);
+    }
+
+    #[test]
+    fn test_grouped_codes_are_joined_or_preserved() {
+        let test_cases = vec![
+            ("Your WhatsApp code: 123-456", "123456", "123-456"),
+            (
+                "您的验证码是 1234 5678，5分钟内有效",
+                "12345678",
+                "1234 5678",
+            ),
+            (
+                "G-482913 is your Google verification code.",
+                "482913",
+                "G-482913",
+            ),
+            ("Your Steam code is 12 34 56", "123456", "12 34 56"),
+        ];
+
+        for (input, expected_code, expected_formatted) in &test_cases {
+            let result = extract_verification_code(input)
+                .unwrap_or_else(|| panic!("should extract a code from {:?}", input));
+            assert_eq!(result.code, *expected_code, "input: {:?}", input);
+            assert_eq!(result.formatted, *expected_formatted, "input: {:?}", input);
+            assert_eq!(&input[result.span.0..result.span.1], *expected_formatted);
+        }
+
+        let mut config = Config::default();
+        config.preserve_code_grouping = true;
+        let extractor = ExtractorChain::new(&config);
+        for (input, _, expected_formatted) in &test_cases {
+            let result = extractor
+                .extract(input, None)
+                .expect("should extract a code");
+            assert_eq!(result.code, *expected_formatted, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_keyword_tiers_and_negative_keywords() {
+        let test_cases = vec![
+            // 弱关键词 "code" 按完整单词匹配
+            ("Failed to decode attachment 482913", None),
+            ("Billing zip code 10001 has been updated", None),
+            // 排除词中的 "code" 不算数，附近的候选码被淘汰
+            ("Use promo code SAVE2024 at checkout", None),
+            ("Get a 20% discount with code SAVE2024", None),
+            (
+                "Use promo code SAVE2024 for 20% off. Your verification code is 482913",
+                Some("482913"),
+            ),
+            // 考虑所有关键词出现的位置，而不只是第一个
+            (
+                "Code of conduct updated. 如非本人操作请忽略。您的验证码为 739201",
+                Some("739201"),
+            ),
+        ];
+
+        for (input, expected) in test_cases {
+            let result = extract_verification_code(input);
+            assert_eq!(
+                result.as_ref().map(|r| r.code.as_str()),
+                expected,
+                "input: {:?}",
+                input
+            );
+        }
+
+        let result = extract_verification_code("code: 1234, 您的验证码为 739201").unwrap();
+        assert_eq!(result.code, "739201");
+        assert_eq!(result.keyword, "验证码");
+    }
+
+    #[test]
+    fn test_extract_all_labels_every_plausible_code() {
+        let extractor = ExtractorChain::new(&Config::default());
+
+        let input = "Your PIN is 1234 and your verification code is 567890";
+        let codes = extractor.extract_all(input, None);
+        let labeled: Vec<(&str, CodeRole)> =
+            codes.iter().map(|c| (c.code.as_str(), c.role)).collect();
+        assert_eq!(
+            labeled,
+            vec![
+                ("567890", CodeRole::VerificationCode),
+                ("1234", CodeRole::Pin)
+            ]
+        );
+        assert!(codes[0].confidence > codes[1].confidence);
+
+        let input = "Your login code is 482913. Backup code: 739201";
+        let mut codes: Vec<String> = extractor
+            .extract_all(input, None)
+            .into_iter()
+            .map(|c| c.code)
+            .collect();
+        codes.sort();
+        assert_eq!(codes, vec!["482913", "739201"]);
+
+        // 账号等被扣分的候选码不作为备选
+        let input = "【倒三角】您的账号W8406772本次登录验证码为666684，请勿泄露";
+        let codes = extractor.extract_all(input, None);
+        assert_eq!(codes.len(), 1);
+    }
+
+    #[test]
+    fn test_extraction_result_reports_validity() {
+        let result = extract_verification_code(
+            "【自如网】自如验证码 356407，有效时间为一分钟，请勿将验证码告知任何人！",
+        )
+        .unwrap();
+        assert_eq!(result.validity, Some(Duration::from_secs(60)));
+        assert_eq!(result.service.as_deref(), Some("自如网"));
+
+        let result = extract_verification_code("Your verification code is 482913").unwrap();
+        assert_eq!(result.validity, None);
+    }
+
+    #[test]
+    fn test_pickup_codes_and_disabled_categories() {
+        let result =
+            extract_verification_code("【菜鸟驿站】您的包裹已到站，凭取件码 3-2-1045 领取")
+                .expect("should extract pickup code");
+        assert_eq!(result.code, "3-2-1045");
+        assert_eq!(result.category, MessageCategory::PickupCode);
+
+        let result = extract_verification_code("【丰巢】凭取件码 12345678 到丰巢柜取件").unwrap();
+        assert_eq!(result.code, "12345678");
+        assert!(!result.category.is_otp());
+
+        let mut config = Config::default();
+        config.categories.pickup_code = false;
+        let extractor = ExtractorChain::new(&config);
+        assert!(
+            extractor
+                .extract("【丰巢】凭取件码 12345678 到丰巢柜取件", None)
+                .is_none()
+        );
+        assert!(extractor.extract("您的登录验证码为 482913", None).is_some());
+    }
+
+    #[test]
+    fn test_keyword_packs_from_config_and_script() {
+        // 没有变音符号的德文无法从文字判断语言，需要在配置中启用
+        let message = "Ihr Verifizierungscode: 482913";
+        assert!(extract_verification_code(message).is_none());
+
+        let mut config = Config::default();
+        config.keyword_packs.languages = vec!["de".to_string()];
+        let result = ExtractorChain::new(&config).extract(message, None).unwrap();
+        assert_eq!(result.code, "482913");
+        assert_eq!(result.keyword, "Verifizierungscode");
+
+        // 日文消息根据假名自动启用日文关键词包，关闭自动启用后找不到关键词
+        let message = "認証番号：482913 この番号を入力してください";
+        assert_eq!(extract_verification_code(message).unwrap().code, "482913");
+        config.keyword_packs.auto = false;
+        assert!(
+            ExtractorChain::new(&config)
+                .extract(message, None)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_sender_rule_overrides_keywords_and_group() {
+        let mut config = Config::default();
+        config.rules = vec![
+            SenderRule {
+                name: Some("steam".to_string()),
+                brand: Some("Steam".to_string()),
+                keywords: Some(vec!["令牌".to_string()]),
+                min_length: Some(5),
+                max_length: Some(5),
+                ..SenderRule::default()
+            },
+            SenderRule {
+                name: Some("bank".to_string()),
+                sender: Some("95588".to_string()),
+                regex: Some(r"动态码\D*(\d{6})".to_string()),
+                group: Some(1),
+                ..SenderRule::default()
+            },
+        ];
+
+        let steam = "【Steam】您的 Steam 令牌 R4T2K 请在登录时输入";
+        let result = ExtractorChain::new(&config)
+            .extract(steam, None)
+            .expect("rule should match");
+        assert_eq!(result.code, "R4T2K");
+        assert_eq!(result.rule.as_deref(), Some("steam"));
+
+        // 不命中规则时使用全局关键词，"令牌" 不在其中
+        let global = extract_verification_code(steam);
+        assert!(global.is_none());
+
+        let bank = "您尾号1234的卡正在进行网上支付，动态码 839201，验证码请勿泄露";
+        let result = ExtractorChain::new(&config)
+            .extract(bank, Some("95588"))
+            .expect("rule should match");
+        assert_eq!(result.code, "839201");
+        assert_eq!(result.rule.as_deref(), Some("bank"));
+    }
+
+    #[test]
+    fn test_invalid_or_runaway_regex_falls_back_to_default() {
+        let mut config = Config::default();
+        config.verification_regex = "(".to_string();
+        let result = ExtractorChain::new(&config).extract("your code is 432141", None);
+        assert_eq!(result.map(|r| r.code).as_deref(), Some("432141"));
+
+        // 反向引用迫使 fancy_regex 回溯，超出回溯上限后改用默认正则
+        config.verification_regex = r"(a+)+\1b|\d{6}".to_string();
+        let message = format!("验证码 {} 123456", "a".repeat(40));
+        let result = ExtractorChain::new(&config).extract(&message, None);
+        assert_eq!(result.map(|r| r.code).as_deref(), Some("123456"));
+    }
+
+    #[test]
+    fn test_cjk_full_width_and_character_distance() {
+        let mut config = Config::default();
+        config.verification_keywords.push("認証コード".to_string());
+        let extractor = ExtractorChain::new(&config);
+
+        let test_cases = vec![
+            // 全角数字
+            (
+                "【自如网】验证码：３５６４０７，有效时间为一分钟",
+                "356407",
+                "３５６４０７",
+            ),
+            // 验证码中夹杂零宽空格和不换行空格
+            (
+                "您的验证码为\u{00A0}４８２\u{200B}９１３，请勿泄露",
+                "482913",
+                "４８２\u{200B}９１３",
+            ),
+            // 日文
+            (
+                "【LINE】認証コード：２９４８１３ このコードを入力してください",
+                "294813",
+                "２９４８１３",
+            ),
+            // 韩文
+            (
+                "[Web발신] [카카오] 인증번호 [１２３４５６]를 입력해주세요.",
+                "123456",
+                "１２３４５６",
+            ),
+            // 关键词与验证码相隔 47 个字符（139 字节）：按字节计算会超过距离上限，按字符计算则不会
+            (
+                "验证码已发送，为了保障您的账户安全，请勿将短信内容告知他人，如非本人操作请忽略本短信，本次的号码是 739201",
+                "739201",
+                "739201",
+            ),
+        ];
+
+        for (input, expected_code, expected_original) in test_cases {
+            let result = extractor
+                .extract(input, None)
+                .unwrap_or_else(|| panic!("should extract a code from {:?}", input));
+            assert_eq!(result.code, expected_code, "input: {:?}", input);
+            assert_eq!(
+                &input[result.span.0..result.span.1],
+                expected_original,
+                "input: {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_pathological_input_finishes_quickly() {
+        let extractor = ExtractorChain::new(&Config::default());
+        let inputs = [
+            "1-".repeat(5000),
+            "12 ".repeat(5000),
+            "验证码".repeat(2000) + "482913",
+            format!("code {}", "9".repeat(20000)),
+            "【".repeat(3000) + "验证码 482913" + &"】".repeat(3000),
+            "https://slack.com/".to_string() + &"a".repeat(20000) + " sign in",
+            "İ".repeat(5000) + " code 482913",
+        ];
+
+        for input in &inputs {
+            let start = Instant::now();
+            extractor.run(input, Some(input));
+            let elapsed = start.elapsed();
+            assert!(
+                elapsed < Duration::from_secs(2),
+                "took {:?} on {:?}...",
+                elapsed,
+                &input.chars().take(20).collect::<String>()
+            );
+        }
+    }
+
+    proptest! {
+        // 任意 Unicode 输入都不能让解析器 panic，监听线程中的 panic 会让监听静默停止
+        #[test]
+        fn prop_arbitrary_input_never_panics(content in any::<String>(), sender in proptest::option::of(any::<String>())) {
+            let extractor = ExtractorChain::new(&Config::default());
+            if let Some(result) = extractor.extract(&content, sender.as_deref()) {
+                prop_assert!(content.get(result.span.0..result.span.1).is_some());
+            }
+            extractor.extract_all(&content, sender.as_deref());
+            extractor.run(&content, None);
+        }
+
+        // 大小写变换会改变字节长度的字符、全角字符、组合字符与数字、分隔符混排
+        #[test]
+        fn prop_mixed_script_input_never_panics(content in "[0-9０-９ 　\\-－—A-Za-zİıẞß\u{0301}\u{200b}验证码取件【】\\[\\]：:，,。.]{0,120}") {
+            let extractor = ExtractorChain::new(&Config::default());
+            if let Some(result) = extractor.extract(&content, None) {
+                prop_assert!(content.get(result.span.0..result.span.1).is_some());
+            }
+        }
+
+        // 已知模板中注入的验证码总能被识别出来
+        #[test]
+        fn prop_injected_code_is_recovered(
+            code in "[0-9]{4,8}",
+            template in 0usize..4,
+            brand in prop::sample::select(vec!["自如网", "腾讯云", "百度账号", "Apple", "GitHub"]),
+            filler in "[a-z ]{0,40}",
+        ) {
+            let content = match template {
+                0 => format!("【{}】您的验证码为{}，5分钟内有效，请勿泄露。", brand, code),
+                1 => format!("【{}】{}是你的验证码，切勿将验证码泄露于他人。", brand, code),
+                2 => format!("{} Your {} verification code is: {}", filler, brand, code),
+                _ => format!("{}: your code is {}. {}", brand, code, filler),
+            };
+            let result = extract_verification_code(&content);
+            prop_assert_eq!(result.map(|r| r.code), Some(code), "content: {:?}", content);
+        }
+    }
+
+    // 手动运行：cargo test --release bench_per_message_cost -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_per_message_cost() {
+        let messages = [
+            "【自如网】自如验证码 356407，有效时间为一分钟，请勿将验证码告知任何人！如非您本人操作，请及时致电4001001111",
+            "【倒三角】易支撑（登录）——您的账号W8406772本次登录验证码为666684，请勿泄露，有效时间5分钟，如非本人操作请忽略本短信。",
+            "If this was you, your verification code is: 047289 If you didn't request i： click here to deny.",
+            "As a token of our appreciation, upon completing the survey, you will get a 10% discount promo code on your first payment.",
+        ];
+        let iterations = 2000;
+
+        let build_start = Instant::now();
+        let extractor = ExtractorChain::new(&Config::default());
+        let build_cost = build_start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            for message in &messages {
+                std::hint::black_box(extractor.extract(message, None));
+            }
+        }
+        let per_message = start.elapsed() / (iterations * messages.len() as u32);
+
+        println!("=== 单条消息提取耗时 ===");
+        println!("构建提取器: {:?}", build_cost);
+        println!("每条消息: {:?}", per_message);
+    }
+}
@@ -0,0 +1,102 @@
+use unicode_normalization::UnicodeNormalization;
+
+// 会被直接删除的零宽字符
+const ZERO_WIDTH_CHARS: &[char] = &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+/// 经过逐字符变换的文本，记录每个字节来自原文的哪个字符。
+///
+/// 用于在 NFKC 归一化、转小写之后，把匹配到的位置映射回原文。
+pub struct MappedText {
+    pub text: String,
+    // sources[i] 为 text 第 i 个字节所属字符在原文中的字节区间
+    sources: Vec<(usize, usize)>,
+    original_len: usize,
+}
+
+impl MappedText {
+    fn map_chars<I>(original: &str, transform: impl Fn(char) -> I) -> Self
+    where
+        I: Iterator<Item = char>,
+    {
+        let mut text = String::with_capacity(original.len());
+        let mut sources = Vec::with_capacity(original.len());
+
+        for (start, c) in original.char_indices() {
+            let source = (start, start + c.len_utf8());
+            for mapped in transform(c) {
+                text.push(mapped);
+                sources.extend(std::iter::repeat_n(source, mapped.len_utf8()));
+            }
+        }
+
+        Self {
+            text,
+            sources,
+            original_len: original.len(),
+        }
+    }
+
+    /// NFKC 归一化：全角数字和字母转为半角，删除零宽字符，各类空格统一为普通空格
+    pub fn normalize(original: &str) -> Self {
+        Self::map_chars(original, |c| {
+            let replaced = match c {
+                c if ZERO_WIDTH_CHARS.contains(&c) => None,
+                '\u{00A0}' | '\u{202F}' | '\u{3000}' => Some(' '),
+                c => Some(c),
+            };
+            replaced.into_iter().nfkc()
+        })
+    }
+
+    /// 转为小写，用于不区分大小写地查找关键词
+    pub fn lowercase(original: &str) -> Self {
+        Self::map_chars(original, char::to_lowercase)
+    }
+
+    /// 将本文本中的字节区间映射回原文中的字节区间
+    pub fn original_span(&self, span: (usize, usize)) -> (usize, usize) {
+        let (start, end) = span;
+        if start >= end {
+            let pos = self
+                .sources
+                .get(start)
+                .map_or(self.original_len, |source| source.0);
+            return (pos, pos);
+        }
+        (self.sources[start].0, self.sources[end - 1].1)
+    }
+}
+
+/// 两个区间之间相隔的字符数，重叠时为 0
+pub fn char_distance(text: &str, a: (usize, usize), b: (usize, usize)) -> usize {
+    let (first, second) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+    if second.0 <= first.1 {
+        return 0;
+    }
+    text[first.1..second.0].chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_maps_back_to_original() {
+        let original = "验证码：１２３\u{200B}４５６";
+        let normalized = MappedText::normalize(original);
+        assert_eq!(normalized.text, "验证码:123456");
+
+        let start = normalized.text.find("123456").unwrap();
+        let span = normalized.original_span((start, start + 6));
+        assert_eq!(&original[span.0..span.1], "１２３\u{200B}４５６");
+    }
+
+    #[test]
+    fn test_char_distance_counts_characters() {
+        let text = "验证码为您的666684";
+        let keyword_end = "验证码".len();
+        let code_start = text.find("666684").unwrap();
+        let distance = char_distance(text, (0, keyword_end), (code_start, text.len()));
+        assert_eq!(distance, 3);
+    }
+}
@@ -0,0 +1,137 @@
+// 这个文件同时被 build.rs 引用，只能依赖 std 和 serde
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 朴素贝叶斯模型的计数，判断消息是否为验证码消息。
+///
+/// 只保存计数而不是概率，新样本可以直接累加到内置模型上重新训练。
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct OtpModel {
+    pub otp_messages: u32,
+    pub other_messages: u32,
+    pub otp_tokens: HashMap<String, u32>,
+    pub other_tokens: HashMap<String, u32>,
+}
+
+impl OtpModel {
+    /// 加入一条已标注的样本
+    pub fn add(&mut self, message: &str, is_otp: bool) {
+        let (messages, tokens) = if is_otp {
+            (&mut self.otp_messages, &mut self.otp_tokens)
+        } else {
+            (&mut self.other_messages, &mut self.other_tokens)
+        };
+        *messages += 1;
+        for token in tokenize(message) {
+            *tokens.entry(token).or_default() += 1;
+        }
+    }
+
+    /// 消息是验证码消息的概率，0 ~ 1；模型为空时返回 None
+    pub fn otp_probability(&self, message: &str) -> Option<f64> {
+        if self.otp_messages == 0 || self.other_messages == 0 {
+            return None;
+        }
+
+        let vocabulary = self
+            .otp_tokens
+            .keys()
+            .chain(
+                self.other_tokens
+                    .keys()
+                    .filter(|t| !self.otp_tokens.contains_key(*t)),
+            )
+            .count() as f64;
+        let otp_total = self.otp_tokens.values().sum::<u32>() as f64;
+        let other_total = self.other_tokens.values().sum::<u32>() as f64;
+
+        // 拉普拉斯平滑后的对数几率
+        let mut log_odds = (self.otp_messages as f64).ln() - (self.other_messages as f64).ln();
+        for token in tokenize(message) {
+            let otp = *self.otp_tokens.get(&token).unwrap_or(&0) as f64;
+            let other = *self.other_tokens.get(&token).unwrap_or(&0) as f64;
+            if otp == 0.0 && other == 0.0 {
+                continue;
+            }
+            log_odds += ((otp + 1.0) / (otp_total + vocabulary)).ln()
+                - ((other + 1.0) / (other_total + vocabulary)).ln();
+        }
+        Some(1.0 / (1.0 + (-log_odds).exp()))
+    }
+}
+
+/// 把消息切分为特征：拉丁字母等按单词，中日韩文和泰文这类不用空格分词的文字取相邻两字，数字只保留长度
+pub fn tokenize(message: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut digits = 0;
+    let mut previous_cjk: Option<char> = None;
+
+    let flush_word = |word: &mut String, tokens: &mut Vec<String>| {
+        if word.chars().count() > 1 {
+            tokens.push(std::mem::take(word));
+        }
+        word.clear();
+    };
+    let flush_digits = |digits: &mut usize, tokens: &mut Vec<String>| {
+        if *digits > 0 {
+            // 验证码常见的 4 ~ 8 位数字是重要特征，具体数字无关紧要
+            tokens.push(format!("#{}", (*digits).min(9)));
+        }
+        *digits = 0;
+    };
+
+    for c in message.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_digit() {
+            flush_word(&mut word, &mut tokens);
+            previous_cjk = None;
+            digits += 1;
+            continue;
+        }
+        flush_digits(&mut digits, &mut tokens);
+
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            if let Some(previous) = previous_cjk {
+                tokens.push(format!("{}{}", previous, c));
+            }
+            previous_cjk = Some(c);
+        } else if c.is_alphanumeric() {
+            previous_cjk = None;
+            word.push(c);
+        } else {
+            previous_cjk = None;
+            flush_word(&mut word, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_digits(&mut digits, &mut tokens);
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}' | '\u{0e00}'..='\u{0e7f}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_and_score() {
+        assert_eq!(tokenize("Your code: 482913"), vec!["your", "code", "#6"]);
+        assert_eq!(tokenize("验证码482913"), vec!["验证", "证码", "#6"]);
+
+        let mut model = OtpModel::default();
+        assert_eq!(model.otp_probability("code 482913"), None);
+        model.add("Your verification code is 482913", true);
+        model.add("您的验证码为 356407", true);
+        model.add("Get 20% off with a discount on your next order", false);
+        model.add("双十一大促，全场 5 折，回复 TD 退订", false);
+
+        let otp = model.otp_probability("Your code is 771204").unwrap();
+        let other = model.otp_probability("20% off your next order").unwrap();
+        assert!(otp > 0.5 && other < 0.5, "otp: {}, other: {}", otp, other);
+    }
+}
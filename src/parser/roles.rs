@@ -0,0 +1,108 @@
+use rust_i18n::t;
+
+// 只在候选码之前这么多个字符内寻找用途标签
+const LABEL_WINDOW: usize = 24;
+
+// 各用途的标签词，取离候选码最近的一个
+const LABELS: &[(&str, CodeRole)] = &[
+    ("验证码", CodeRole::VerificationCode),
+    ("校验码", CodeRole::VerificationCode),
+    ("动态码", CodeRole::VerificationCode),
+    ("动态密码", CodeRole::VerificationCode),
+    ("verification code", CodeRole::VerificationCode),
+    ("login code", CodeRole::VerificationCode),
+    ("backup code", CodeRole::VerificationCode),
+    ("otp", CodeRole::VerificationCode),
+    ("code", CodeRole::VerificationCode),
+    ("pin", CodeRole::Pin),
+    ("密码", CodeRole::Pin),
+    ("取件码", CodeRole::PickupCode),
+    ("取货码", CodeRole::PickupCode),
+    ("提货码", CodeRole::PickupCode),
+    ("取餐码", CodeRole::PickupCode),
+    ("pickup code", CodeRole::PickupCode),
+    ("collection code", CodeRole::PickupCode),
+    ("参考号", CodeRole::ReferenceNumber),
+    ("流水号", CodeRole::ReferenceNumber),
+    ("编号", CodeRole::ReferenceNumber),
+    ("reference", CodeRole::ReferenceNumber),
+    ("ref", CodeRole::ReferenceNumber),
+    ("case", CodeRole::ReferenceNumber),
+    ("ticket", CodeRole::ReferenceNumber),
+];
+
+/// 候选码在消息中的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeRole {
+    VerificationCode,
+    Pin,
+    PickupCode,
+    ReferenceNumber,
+}
+
+impl CodeRole {
+    /// 用于界面显示的本地化名称
+    pub fn label(&self) -> String {
+        match self {
+            CodeRole::VerificationCode => t!("code_role.verification_code").to_string(),
+            CodeRole::Pin => t!("code_role.pin").to_string(),
+            CodeRole::PickupCode => t!("code_role.pickup_code").to_string(),
+            CodeRole::ReferenceNumber => t!("code_role.reference_number").to_string(),
+        }
+    }
+}
+
+/// 根据候选码之前最近的标签词判断其用途，没有标签时视为验证码
+pub fn classify(text_before: &str) -> CodeRole {
+    let start = text_before
+        .char_indices()
+        .rev()
+        .nth(LABEL_WINDOW - 1)
+        .map_or(0, |(i, _)| i);
+    let window = text_before[start..].to_lowercase();
+
+    LABELS
+        .iter()
+        .filter_map(|(label, role)| rfind_word(&window, label).map(|end| (end, label.len(), *role)))
+        // 结束位置相同时取更长的标签，"pickup code" 优先于 "code"
+        .max_by_key(|(end, len, _)| (*end, *len))
+        .map_or(CodeRole::VerificationCode, |(_, _, role)| role)
+}
+
+// 返回标签最后一次完整出现的结束位置，英文标签需要完整单词匹配
+fn rfind_word(text: &str, label: &str) -> Option<usize> {
+    let is_word = label.starts_with(|c: char| c.is_ascii_alphanumeric());
+    text.rmatch_indices(label)
+        .find(|(pos, _)| {
+            if !is_word {
+                return true;
+            }
+            let end = pos + label.len();
+            let before_ok = !text[..*pos]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_ascii_alphanumeric());
+            let after_ok = !text[end..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphanumeric());
+            before_ok && after_ok
+        })
+        .map(|(pos, _)| pos + label.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_uses_nearest_label() {
+        let text = "your PIN is 1234 and your verification code is ";
+        assert_eq!(classify(text), CodeRole::VerificationCode);
+        assert_eq!(classify("your PIN is "), CodeRole::Pin);
+        assert_eq!(classify("您的取件码为"), CodeRole::PickupCode);
+        assert_eq!(classify("Your pickup code: "), CodeRole::PickupCode);
+        assert_eq!(classify("Reference number "), CodeRole::ReferenceNumber);
+        assert_eq!(classify("shopping list "), CodeRole::VerificationCode);
+    }
+}
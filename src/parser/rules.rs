@@ -0,0 +1,90 @@
+use crate::config::SenderRule;
+
+/// 提取短信中的【品牌】前缀，例如 "【腾讯云】验证码..." 返回 "腾讯云"
+pub fn extract_bracket_brand(content: &str) -> Option<&str> {
+    let start = content.find('【')? + '【'.len_utf8();
+    let end = start + content[start..].find('】')?;
+    let brand = content[start..end].trim();
+    if brand.is_empty() { None } else { Some(brand) }
+}
+
+/// 邮件 From 头 "Name <addr>" 中的地址，其他形式的发件人原样返回
+pub fn sender_address(sender: &str) -> &str {
+    match (sender.rfind('<'), sender.rfind('>')) {
+        (Some(open), Some(close)) if open < close => sender[open + 1..close].trim(),
+        _ => sender.trim(),
+    }
+}
+
+/// 按配置顺序返回第一条匹配当前发件人的规则的下标。
+///
+/// `service` 为识别出的服务名称，见 [`super::service::identify_service`]，规则中的 `brand` 与之比较。
+/// 邮件发件人只用地址匹配，显示名称可以随意伪造。
+pub fn find_matching_rule(
+    rules: &[SenderRule],
+    service: Option<&str>,
+    sender: Option<&str>,
+) -> Option<usize> {
+    let sender = sender.map(|s| sender_address(s).to_lowercase());
+    let brand = service.map(|b| b.to_lowercase());
+
+    rules.iter().position(|rule| {
+        // 规则至少要声明一个匹配条件，否则会误伤所有消息
+        if rule.sender.is_none() && rule.brand.is_none() {
+            return false;
+        }
+
+        let sender_matches = match (&rule.sender, &sender) {
+            (Some(pattern), Some(sender)) => sender.contains(&pattern.to_lowercase()),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let brand_matches = match (&rule.brand, &brand) {
+            (Some(pattern), Some(brand)) => brand == &pattern.to_lowercase(),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        sender_matches && brand_matches
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_matches_sender_or_brand() {
+        let rules = vec![
+            SenderRule {
+                name: Some("citi".to_string()),
+                sender: Some("692484".to_string()),
+                ..SenderRule::default()
+            },
+            SenderRule {
+                name: Some("steam".to_string()),
+                brand: Some("Steam".to_string()),
+                ..SenderRule::default()
+            },
+            SenderRule::default(),
+        ];
+
+        let citi = find_matching_rule(&rules, None, Some("+1692484"));
+        assert_eq!(citi, Some(0));
+
+        let steam = find_matching_rule(&rules, Some("steam"), None);
+        assert_eq!(steam, Some(1));
+
+        assert!(find_matching_rule(&rules, None, Some("10690")).is_none());
+
+        // 邮件只匹配地址，不匹配显示名称
+        let bank = vec![SenderRule {
+            sender: Some("@citibank.com".to_string()),
+            ..SenderRule::default()
+        }];
+        let from = "Citi <alerts@citibank.com>";
+        assert_eq!(find_matching_rule(&bank, None, Some(from)), Some(0));
+        let spoofed = "\"alerts@citibank.com\" <phish@example.com>";
+        assert!(find_matching_rule(&bank, None, Some(spoofed)).is_none());
+    }
+}
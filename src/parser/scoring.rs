@@ -0,0 +1,198 @@
+use super::normalize::char_distance;
+use crate::config::ScoringWeights;
+
+// 候选码与排除词相隔不超过该字符数时扣分
+const NEGATIVE_KEYWORD_RANGE: usize = 20;
+
+// 紧邻验证码之前、表示"下面就是验证码"的引导词
+const LEAD_IN_MARKERS: &[&str] = &[":", "：", "为", "是", "is"];
+
+// 紧跟验证码之后、表示"前面就是验证码"的引导词
+const LEAD_OUT_MARKERS: &[&str] = &["是", "is your", "为您的", "(", "（"];
+
+// 紧邻候选码之前、说明它是账号/手机号等标识而非验证码的词
+const IDENTIFIER_MARKERS: &[&str] = &[
+    "账号", "账户", "用户", "尾号", "手机", "号码", "订单", "单号", "id", "no.", "account",
+    "phone", "tel", "order",
+];
+
+/// 评分时可见的候选码上下文
+pub struct CandidateContext<'a> {
+    pub text: &'a str,
+    pub code: &'a str,
+    /// 候选码在原文中的区间
+    pub span: (usize, usize),
+    /// 锚定关键词在原文中的区间
+    pub keyword_span: (usize, usize),
+    /// 锚定关键词是否为强关键词
+    pub strong_keyword: bool,
+    /// 所有排除词在原文中的区间
+    pub negative_spans: &'a [(usize, usize)],
+}
+
+impl CandidateContext<'_> {
+    /// 候选码与关键词之间相隔的字符数，重叠时为 0
+    pub fn distance(&self) -> usize {
+        char_distance(self.text, self.span, self.keyword_span)
+    }
+
+    /// 候选码是否位于关键词之后（重叠视为之后）
+    pub fn is_after_keyword(&self) -> bool {
+        self.span.1 > self.keyword_span.0
+    }
+
+    /// 候选码附近是否出现了 "promo code"、"优惠码" 等排除词
+    pub fn is_near_negative_keyword(&self) -> bool {
+        self.negative_spans
+            .iter()
+            .any(|span| char_distance(self.text, self.span, *span) <= NEGATIVE_KEYWORD_RANGE)
+    }
+
+    fn text_before(&self) -> &str {
+        &self.text[..self.span.0]
+    }
+
+    fn text_after(&self) -> &str {
+        &self.text[self.span.1..]
+    }
+}
+
+/// 候选码评分模型，分数越高越可能是验证码
+pub trait CandidateScorer {
+    fn score(&self, candidate: &CandidateContext) -> f64;
+}
+
+/// 按 `ScoringWeights` 线性组合各项特征的默认评分模型
+pub struct WeightedScorer {
+    weights: ScoringWeights,
+}
+
+impl WeightedScorer {
+    pub fn new(weights: ScoringWeights) -> Self {
+        Self { weights }
+    }
+}
+
+impl CandidateScorer for WeightedScorer {
+    fn score(&self, candidate: &CandidateContext) -> f64 {
+        let w = &self.weights;
+        let mut score = -w.distance * candidate.distance() as f64;
+
+        score += if candidate.is_after_keyword() {
+            w.after_keyword
+        } else {
+            w.before_keyword
+        };
+
+        score += w.digit_ratio * digit_ratio(candidate.code);
+        score += w.length * length_preference(candidate.code, w.preferred_length);
+
+        if has_lead_in(candidate.text_before()) || has_lead_out(candidate.text_after()) {
+            score += w.punctuation;
+        }
+
+        if looks_like_identifier(candidate.text_before()) {
+            score -= w.identifier_penalty;
+        }
+
+        if candidate.strong_keyword {
+            score += w.strong_keyword;
+        }
+
+        if candidate.is_near_negative_keyword() {
+            score -= w.negative_keyword;
+        }
+
+        score
+    }
+}
+
+fn digit_ratio(code: &str) -> f64 {
+    let total = code.chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let digits = code.chars().filter(|c| c.is_ascii_digit()).count();
+    digits as f64 / total as f64
+}
+
+fn length_preference(code: &str, preferred_length: usize) -> f64 {
+    // 长度等于偏好长度时为 1.0，每偏离一位按比例递减
+    if preferred_length == 0 {
+        return 0.0;
+    }
+    let length = code.chars().filter(|c| c.is_ascii_alphanumeric()).count();
+    let diff = length.abs_diff(preferred_length) as f64;
+    (1.0 - diff / preferred_length as f64).max(0.0)
+}
+
+fn has_lead_in(before: &str) -> bool {
+    let before = before.trim_end().to_lowercase();
+    LEAD_IN_MARKERS
+        .iter()
+        .any(|marker| ends_with_word(&before, marker))
+}
+
+fn has_lead_out(after: &str) -> bool {
+    let after = after.trim_start().to_lowercase();
+    LEAD_OUT_MARKERS
+        .iter()
+        .any(|marker| after.starts_with(marker))
+}
+
+fn looks_like_identifier(before: &str) -> bool {
+    let before = before
+        .trim_end_matches(|c: char| c.is_whitespace() || matches!(c, ':' | '：' | '#'))
+        .to_lowercase();
+    IDENTIFIER_MARKERS
+        .iter()
+        .any(|marker| ends_with_word(&before, marker))
+}
+
+fn ends_with_word(text: &str, word: &str) -> bool {
+    // 英文单词需要完整匹配，避免 "this" 被当成 "is"
+    if !text.ends_with(word) {
+        return false;
+    }
+    if !word.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return true;
+    }
+    !text[..text.len() - word.len()]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score_of(text: &str, code: &str, keyword: &str) -> f64 {
+        let code_start = text.find(code).unwrap();
+        let keyword_start = text.find(keyword).unwrap();
+        let context = CandidateContext {
+            text,
+            code,
+            span: (code_start, code_start + code.len()),
+            keyword_span: (keyword_start, keyword_start + keyword.len()),
+            strong_keyword: true,
+            negative_spans: &[],
+        };
+        WeightedScorer::new(ScoringWeights::default()).score(&context)
+    }
+
+    #[test]
+    fn test_account_id_scores_below_code_after_keyword() {
+        let text = "您的账号W8406772本次登录验证码为666684，请勿泄露";
+        assert!(score_of(text, "666684", "验证码") > score_of(text, "W8406772", "验证码"));
+    }
+
+    #[test]
+    fn test_lead_in_word_requires_boundary() {
+        assert!(has_lead_in("your code is "));
+        assert!(has_lead_in("验证码："));
+        assert!(!has_lead_in("this "));
+        assert!(looks_like_identifier("账号 ID："));
+        assert!(!looks_like_identifier("paid "));
+    }
+}
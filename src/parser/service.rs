@@ -0,0 +1,110 @@
+use super::rules::extract_bracket_brand;
+use fancy_regex::Regex;
+use std::sync::LazyLock;
+
+// 短信末尾的 [XXX统一门户] 签名
+static TRAILING_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\[【]([^\[\]【】]{2,20})[\]】][\s。.]*$").unwrap());
+
+// 国家代码顶级域名下常见的二级域名，如 co.uk、com.cn
+const SECOND_LEVEL_DOMAINS: &[&str] = &["co", "com", "net", "org", "gov", "edu", "ac"];
+
+/// 识别发送验证码的服务名称。
+///
+/// 依次尝试【品牌】前缀、消息末尾的 [签名]、邮件 From 头的显示名称和发件域名。
+pub fn identify_service(content: &str, sender: Option<&str>) -> Option<String> {
+    if let Some(brand) = extract_bracket_brand(content) {
+        return Some(brand.to_string());
+    }
+
+    if let Ok(Some(captures)) = TRAILING_TAG_RE.captures(content.trim_end()) {
+        // "[482913]" 这样看起来像验证码本身的不算签名
+        let tag = captures.get(1).map(|m| m.as_str().trim());
+        if let Some(tag) = tag.filter(|t| !t.chars().all(|c| c.is_ascii_alphanumeric())) {
+            return Some(tag.to_string());
+        }
+    }
+
+    let sender = sender?;
+    display_name(sender).or_else(|| sender_domain(sender))
+}
+
+// "Slack" <no-reply@slack.com> 中的 Slack
+fn display_name(from: &str) -> Option<String> {
+    let (name, _) = from.split_once('<')?;
+    let name = name.trim().trim_matches('"').trim();
+    if name.is_empty() || name.contains('@') {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+// no-reply@accounts.google.com 中的 google.com
+fn sender_domain(from: &str) -> Option<String> {
+    let address = from
+        .rsplit('<')
+        .next()?
+        .trim_end_matches(|c: char| c == '>' || c.is_whitespace());
+    let (_, domain) = address.rsplit_once('@')?;
+    let labels: Vec<&str> = domain.split('.').filter(|l| !l.is_empty()).collect();
+    if labels.len() < 2 {
+        return None;
+    }
+
+    let keep = match labels.as_slice() {
+        [.., second, last]
+            if labels.len() >= 3 && last.len() == 2 && SECOND_LEVEL_DOMAINS.contains(second) =>
+        {
+            3
+        }
+        _ => 2,
+    };
+    Some(labels[labels.len() - keep..].join(".").to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_service_from_brackets_tags_and_sender() {
+        let cases = vec![
+            (
+                "【自如网】自如验证码 356407，有效时间为一分钟",
+                None,
+                Some("自如网"),
+            ),
+            (
+                "您的动态验证码为 482913，请勿泄露。[XX大学统一门户]",
+                None,
+                Some("XX大学统一门户"),
+            ),
+            (
+                "Your code is 482913",
+                Some("\"Slack\" <no-reply@slack.com>"),
+                Some("Slack"),
+            ),
+            (
+                "Your code is 482913",
+                Some("<no-reply@accounts.google.com>"),
+                Some("google.com"),
+            ),
+            (
+                "Your code is 482913",
+                Some("alerts@service.bank.co.uk"),
+                Some("bank.co.uk"),
+            ),
+            ("您的验证码为 [482913]", Some("+8610690000"), None),
+        ];
+
+        for (content, sender, expected) in cases {
+            assert_eq!(
+                identify_service(content, sender).as_deref(),
+                expected,
+                "content: {:?}",
+                content
+            );
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use super::ExtractorChain;
+use crate::config::{Config, SenderRule};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// 在多个处理器之间共享的 [`ExtractorChain`]。
+///
+/// 每次取用时只比较配置文件的修改时间，文件变化（托盘切换选项或手动编辑）后才重新加载配置并重建提取器。
+#[derive(Clone)]
+pub struct SharedExtractor {
+    inner: Arc<Mutex<Cached>>,
+}
+
+struct Cached {
+    extractor: Arc<ExtractorChain>,
+    modified: Option<SystemTime>,
+}
+
+impl SharedExtractor {
+    pub fn load() -> Self {
+        let config = Config::load().unwrap_or_default();
+        Self {
+            inner: Arc::new(Mutex::new(Cached {
+                extractor: Arc::new(ExtractorChain::new(&config)),
+                modified: config_modified_time(),
+            })),
+        }
+    }
+
+    /// 返回当前配置对应的提取器，配置文件变化时先重建
+    pub fn current(&self) -> Arc<ExtractorChain> {
+        let mut cached = self.inner.lock().unwrap();
+        let modified = config_modified_time();
+        if modified != cached.modified {
+            log::info!("Config file changed, rebuilding code extractor");
+            let config = Config::load().unwrap_or_default();
+            cached.extractor = Arc::new(ExtractorChain::new(&config));
+            // 加载过程中可能迁移并重写了配置文件，以重写后的时间为准
+            cached.modified = config_modified_time();
+        }
+        cached.extractor.clone()
+    }
+
+    /// 把纠正生成的规则插入到所有规则之前并保存配置，同一发件人、同一模板的旧规则会被替换。
+    ///
+    /// 读写配置和重建提取器都在锁内完成，监听线程不会读到写了一半的配置文件
+    pub fn add_learned_rule(&self, rule: SenderRule) -> Result<(), Box<dyn std::error::Error>> {
+        let mut cached = self.inner.lock().unwrap();
+        let mut config = Config::load()?;
+        config.rules.retain(|r| r.name != rule.name);
+        config.rules.insert(0, rule);
+        config.save()?;
+
+        cached.extractor = Arc::new(ExtractorChain::new(&config));
+        cached.modified = config_modified_time();
+        Ok(())
+    }
+}
+
+fn config_modified_time() -> Option<SystemTime> {
+    fs::metadata(Config::get_config_path())
+        .and_then(|meta| meta.modified())
+        .ok()
+}
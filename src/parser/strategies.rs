@@ -0,0 +1,737 @@
+//! 内置的提取策略。
+//!
+//! 策略名称与配置中 `[chain] order` 的写法相同：
+//! `learned_template`、`sender_rule`、`email_subject`、`keyword_proximity`、`html_emphasis`、`sign_in_link`。
+
+use super::chain::{CodeExtractor, Extraction, Message};
+use super::keyword_packs::{self, KeywordPack, Script};
+use super::keywords::{self, KeywordSet};
+use super::normalize::MappedText;
+use super::scoring::WeightedScorer;
+use super::{
+    ExtractionResult, LabeledCode, MaskedEntity, MessageCategory, RejectedCandidate, SignInLink,
+};
+use super::{category, grouping, links, mask, roles, rules, validity};
+use crate::config::{Config, DEFAULT_VERIFICATION_REGEX, SenderRule, SignInLinkOptions};
+use fancy_regex::Regex;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+pub const LEARNED_TEMPLATE: &str = "learned_template";
+pub const SENDER_RULE: &str = "sender_rule";
+pub const EMAIL_SUBJECT: &str = "email_subject";
+pub const KEYWORD_PROXIMITY: &str = "keyword_proximity";
+pub const HTML_EMPHASIS: &str = "html_emphasis";
+pub const SIGN_IN_LINK: &str = "sign_in_link";
+
+// 验证码出现在强调文字中时，这一证据单独给出的置信度
+const EMPHASIS_CONFIDENCE: f32 = 0.5;
+// 邮件主题中的验证码额外叠加的置信度
+const SUBJECT_CONFIDENCE: f32 = 0.5;
+
+/// 以关键词为锚点、按距离和上下文评分选出验证码，是其他基于规则的策略的基础。
+///
+/// 正则和关键词在构建时编译一次，之后每条消息直接复用。
+pub struct KeywordProximityExtractor {
+    config: Config,
+    regex: Regex,
+    // 运行时出错（如回溯超限）时用于重试的默认正则
+    default_regex: Regex,
+    keywords: KeywordSet,
+    // 取件码消息额外使用 "取件码" 等关键词
+    pickup_keywords: KeywordSet,
+    // 按消息使用的文字自动启用的关键词包，如日文消息加上 "認証コード"
+    script_keywords: HashMap<Script, KeywordSet>,
+    scorer: WeightedScorer,
+}
+
+// 预编译的发件人规则，未设置的项为 None，使用全局配置
+struct CompiledRule {
+    name: String,
+    regex: Option<Regex>,
+    keywords: Option<KeywordSet>,
+}
+
+impl KeywordProximityExtractor {
+    pub fn new(config: &Config) -> Self {
+        let default_regex = Regex::new(DEFAULT_VERIFICATION_REGEX).unwrap();
+
+        // 配置中指定的语言包和系统语言对应的语言包始终启用
+        let options = &config.keyword_packs;
+        let system_pack = keyword_packs::system_language()
+            .filter(|_| options.auto)
+            .and_then(keyword_packs::for_language);
+        let packs: Vec<&KeywordPack> = options
+            .languages
+            .iter()
+            .filter_map(|language| {
+                let pack = keyword_packs::for_language(language);
+                if pack.is_none() {
+                    log::warn!("Unknown keyword pack: {}", language);
+                }
+                pack
+            })
+            .chain(system_pack)
+            .collect();
+        let (keywords, weak) = keyword_packs::merge(
+            &config.verification_keywords,
+            &config.keyword_tiers.weak,
+            packs.iter().copied(),
+        );
+        let keyword_set = |keywords: &[String], weak: &[String]| {
+            KeywordSet::new(keywords, weak, &config.keyword_tiers.negative)
+        };
+
+        let mut script_keywords = HashMap::new();
+        if options.auto {
+            for pack in keyword_packs::PACKS {
+                script_keywords.entry(pack.script).or_insert_with(|| {
+                    let (keywords, weak) = keyword_packs::merge(
+                        &keywords,
+                        &weak,
+                        keyword_packs::for_script(pack.script),
+                    );
+                    keyword_set(&keywords, &weak)
+                });
+            }
+        }
+
+        Self {
+            config: config.clone(),
+            regex: Regex::new(&config.verification_regex).unwrap_or_else(|e| {
+                log::warn!(
+                    "Invalid verification_regex {:?}, falling back to default: {}",
+                    config.verification_regex,
+                    e
+                );
+                default_regex.clone()
+            }),
+            default_regex,
+            keywords: keyword_set(&keywords, &weak),
+            pickup_keywords: keyword_set(
+                &[keywords.as_slice(), &category::pickup_keywords()].concat(),
+                &weak,
+            ),
+            script_keywords,
+            scorer: WeightedScorer::new(config.scoring.clone()),
+        }
+    }
+
+    fn compile_rule(&self, rule: &SenderRule) -> CompiledRule {
+        CompiledRule {
+            name: rule
+                .name
+                .clone()
+                .or_else(|| rule.sender.clone())
+                .or_else(|| rule.brand.clone())
+                .unwrap_or_default(),
+            // 规则中的正则无效时记录警告并退回全局正则
+            regex: rule
+                .regex
+                .as_ref()
+                .and_then(|pattern| match Regex::new(pattern) {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        log::warn!("Invalid regex in sender rule {:?}: {}", rule, e);
+                        None
+                    }
+                }),
+            // 规则中的关键词都按强关键词处理，排除词仍沿用全局配置
+            keywords: rule.keywords.as_deref().map(|keywords| {
+                KeywordSet::new(keywords, &[], &self.config.keyword_tiers.negative)
+            }),
+        }
+    }
+
+    fn extract_with_rule(
+        &self,
+        message: &Message<'_>,
+        rule: Option<(&SenderRule, &CompiledRule)>,
+    ) -> Option<ExtractionResult> {
+        let category = message.category;
+        let default_keywords = if category == MessageCategory::PickupCode {
+            &self.pickup_keywords
+        } else {
+            keyword_packs::detect_script(message.content)
+                .and_then(|script| self.script_keywords.get(&script))
+                .unwrap_or(&self.keywords)
+        };
+        let compiled_rule = rule.map(|(_, compiled)| compiled);
+        let rule = rule.map(|(rule, _)| rule);
+        let keywords = compiled_rule
+            .and_then(|r| r.keywords.as_ref())
+            .unwrap_or(default_keywords);
+        let re = compiled_rule
+            .and_then(|r| r.regex.as_ref())
+            .unwrap_or(&self.regex);
+        let group = rule.and_then(|r| r.group).unwrap_or(0);
+
+        // 先做 NFKC 归一化，之后的位置都基于归一化文本，最后再映射回原文
+        let normalized = MappedText::normalize(message.content);
+
+        // 遮盖日期、电话、金额、链接等实体，遮盖后的文本与归一化文本位置一一对应
+        let (masked_content, masked) = mask::mask_entities(&normalized.text, &self.config.masking);
+        let content = masked_content.as_str();
+
+        let (keyword_matches, negative_spans) = keywords.find(content);
+        if keyword_matches.is_empty() {
+            return None;
+        }
+
+        let mut rejected = Vec::new();
+
+        let mut candidates = match super::extract_candidate_codes(content, re, group, &mut rejected)
+        {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                // 回溯超限等运行时错误只影响当前消息，改用默认正则重试
+                log::warn!(
+                    "Regex error while extracting candidates, retrying with default pattern: {}",
+                    e
+                );
+                rejected.clear();
+                match super::extract_candidate_codes(content, &self.default_regex, 0, &mut rejected)
+                {
+                    Ok(candidates) => candidates,
+                    Err(e) => {
+                        log::error!("Default regex failed on message: {}", e);
+                        return None;
+                    }
+                }
+            }
+        };
+
+        // 规则自带正则时以规则为准，否则把 "123 456"、"G-482913" 这类分组写法合并为完整候选码
+        if compiled_rule.is_none_or(|r| r.regex.is_none()) {
+            candidates = super::merge_grouped_codes(content, candidates, &mut rejected);
+        } else if rule.is_some_and(|r| r.learned) {
+            // 纠正时的验证码是分组写法时，学习到的正则捕获整段分组，这里去掉分隔符
+            for candidate in &mut candidates {
+                if let Some(canonical) = grouping::canonical_code(&candidate.code) {
+                    candidate.code = canonical;
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let filtered_candidates =
+            super::filter_candidates_step1(candidates, rule, category, &mut rejected);
+        if filtered_candidates.is_empty() {
+            return None;
+        }
+
+        let selected = super::select_best_candidate(
+            content,
+            filtered_candidates,
+            &keyword_matches,
+            &negative_spans,
+            &self.scorer,
+            &mut rejected,
+        )?;
+
+        let label = |code: String, span: (usize, usize)| {
+            let formatted = normalized.text[span.0..span.1].to_string();
+            let code = if self.config.preserve_code_grouping {
+                formatted.clone()
+            } else {
+                code
+            };
+            (code, formatted, roles::classify(&content[..span.0]))
+        };
+
+        let alternates = selected
+            .alternates
+            .into_iter()
+            .map(|(candidate, score)| {
+                let (code, formatted, role) = label(candidate.code, candidate.span);
+                LabeledCode {
+                    code,
+                    formatted,
+                    span: normalized.original_span(candidate.span),
+                    role,
+                    confidence: super::score_confidence(score, Some(selected.score)),
+                }
+            })
+            .collect();
+        let (code, formatted, role) = label(selected.code, selected.span);
+
+        Some(ExtractionResult {
+            span: normalized.original_span(selected.span),
+            code,
+            formatted,
+            keyword: selected.keyword,
+            distance: selected.distance,
+            score: selected.score,
+            rejected: rejected
+                .into_iter()
+                .map(|candidate| RejectedCandidate {
+                    span: normalized.original_span(candidate.span),
+                    ..candidate
+                })
+                .collect(),
+            masked: masked
+                .into_iter()
+                .map(|entity| MaskedEntity {
+                    span: normalized.original_span(entity.span),
+                    ..entity
+                })
+                .collect(),
+            rule: compiled_rule.map(|r| r.name.clone()),
+            strategies: Vec::new(),
+            service: message.service.clone(),
+            category,
+            confidence: super::score_confidence(selected.score, selected.runner_up_score),
+            role,
+            alternates,
+            validity: validity::find_validity(&normalized.text),
+        })
+    }
+}
+
+impl KeywordProximityExtractor {
+    // 不带发件人规则的结果在同一条消息的各策略之间共用，只计算一次
+    fn extract_shared(&self, message: &Message<'_>) -> Option<ExtractionResult> {
+        message
+            .keyword_result
+            .get_or_init(|| self.extract_with_rule(message, None))
+            .clone()
+    }
+}
+
+impl CodeExtractor for KeywordProximityExtractor {
+    fn name(&self) -> &str {
+        KEYWORD_PROXIMITY
+    }
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+        self.extract_shared(message)
+            .map(|result| Extraction::Code(Box::new(result)))
+    }
+}
+
+// 一组发件人规则及其预编译结果，下标一一对应
+struct RuleSet {
+    pipeline: Arc<KeywordProximityExtractor>,
+    rules: Vec<SenderRule>,
+    compiled: Vec<CompiledRule>,
+}
+
+impl RuleSet {
+    fn new(pipeline: Arc<KeywordProximityExtractor>, rules: Vec<SenderRule>) -> Self {
+        let compiled = rules
+            .iter()
+            .map(|rule| pipeline.compile_rule(rule))
+            .collect();
+        Self {
+            pipeline,
+            rules,
+            compiled,
+        }
+    }
+
+    /// 从第 `first` 条起第一条匹配消息发件人的规则
+    fn find_from(&self, message: &Message<'_>, first: usize) -> Option<usize> {
+        rules::find_matching_rule(
+            &self.rules[first..],
+            message.service.as_deref(),
+            message.sender,
+        )
+        .map(|i| first + i)
+    }
+
+    fn extract(&self, message: &Message<'_>, index: usize) -> Option<ExtractionResult> {
+        self.pipeline
+            .extract_with_rule(message, Some((&self.rules[index], &self.compiled[index])))
+    }
+}
+
+/// 配置中手写的 `[[rules]]`，按配置顺序使用第一条匹配发件人的规则。
+///
+/// 命中规则后以规则为准：即使没有提取到验证码，也不再运行后面的策略。
+pub struct SenderRuleExtractor {
+    rules: RuleSet,
+}
+
+impl SenderRuleExtractor {
+    /// `rules` 中由纠正生成的规则会被忽略，由 [`LearnedTemplateExtractor`] 处理
+    pub fn new(pipeline: Arc<KeywordProximityExtractor>, rules: &[SenderRule]) -> Self {
+        let rules = rules.iter().filter(|r| !r.learned).cloned().collect();
+        Self {
+            rules: RuleSet::new(pipeline, rules),
+        }
+    }
+}
+
+impl CodeExtractor for SenderRuleExtractor {
+    fn name(&self) -> &str {
+        SENDER_RULE
+    }
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+        let index = self.rules.find_from(message, 0)?;
+        self.rules
+            .extract(message, index)
+            .map(|result| Extraction::Code(Box::new(result)))
+    }
+
+    fn is_decisive(&self, message: &Message<'_>, _extraction: Option<&Extraction>) -> bool {
+        self.rules.find_from(message, 0).is_some()
+    }
+}
+
+/// 悬浮窗中的纠正生成的规则（`learned = true`），见 [`super::corrections`]。
+///
+/// 学习到的规则只适用于纠正时的消息模板，同一发件人的规则依次尝试，都提取不到时交给后面的策略。
+pub struct LearnedTemplateExtractor {
+    rules: RuleSet,
+}
+
+impl LearnedTemplateExtractor {
+    pub fn new(pipeline: Arc<KeywordProximityExtractor>, rules: &[SenderRule]) -> Self {
+        let rules = rules.iter().filter(|r| r.learned).cloned().collect();
+        Self {
+            rules: RuleSet::new(pipeline, rules),
+        }
+    }
+}
+
+impl CodeExtractor for LearnedTemplateExtractor {
+    fn name(&self) -> &str {
+        LEARNED_TEMPLATE
+    }
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+        let mut first = 0;
+        while let Some(index) = self.rules.find_from(message, first) {
+            if let Some(result) = self.rules.extract(message, index) {
+                return Some(Extraction::Code(Box::new(result)));
+            }
+            first = index + 1;
+        }
+        None
+    }
+
+    // 用户纠正过的模板以纠正结果为准
+    fn is_decisive(&self, _message: &Message<'_>, extraction: Option<&Extraction>) -> bool {
+        extraction.is_some()
+    }
+}
+
+/// 只在邮件主题中提取验证码，如 "123456 is your Facebook code"。
+///
+/// 主题短且专门用来说明邮件用途，其中的验证码比正文中的更可信，置信度在关键词距离评分的基础上提高。
+/// 主题位于消息开头，结果中的位置可以直接用于整条消息。
+pub struct SubjectExtractor {
+    pipeline: Arc<KeywordProximityExtractor>,
+}
+
+impl SubjectExtractor {
+    pub fn new(pipeline: Arc<KeywordProximityExtractor>) -> Self {
+        Self { pipeline }
+    }
+}
+
+impl CodeExtractor for SubjectExtractor {
+    fn name(&self) -> &str {
+        EMAIL_SUBJECT
+    }
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+        let subject = message.subject?;
+        let subject_message = Message {
+            content: subject,
+            category: category::classify(subject),
+            emphasized: &[],
+            subject: None,
+            keyword_result: OnceLock::new(),
+            ..message.clone()
+        };
+        let result = self.pipeline.extract_with_rule(&subject_message, None)?;
+        Some(Extraction::Code(Box::new(ExtractionResult {
+            confidence: 1.0 - (1.0 - result.confidence) * (1.0 - SUBJECT_CONFIDENCE),
+            category: message.category,
+            // 有效期通常写在正文中
+            validity: result
+                .validity
+                .or_else(|| validity::find_validity(&MappedText::normalize(message.content).text)),
+            ..result
+        })))
+    }
+}
+
+/// HTML 邮件中加粗、大号或等宽字体的文字常是验证码所在的元素。
+///
+/// 在关键词距离选出的验证码和备选验证码中，找出出现在强调文字中的一个：
+/// 选中的验证码与关键词距离的结果合并后置信度提高；
+/// 只有备选验证码被强调时，把它提升为选中的验证码，原来选中的验证码改为备选。
+pub struct HtmlEmphasisExtractor {
+    pipeline: Arc<KeywordProximityExtractor>,
+}
+
+impl HtmlEmphasisExtractor {
+    pub fn new(pipeline: Arc<KeywordProximityExtractor>) -> Self {
+        Self { pipeline }
+    }
+}
+
+impl CodeExtractor for HtmlEmphasisExtractor {
+    fn name(&self) -> &str {
+        HTML_EMPHASIS
+    }
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+        if message.emphasized.is_empty() {
+            return None;
+        }
+        let result = self.pipeline.extract_shared(message)?;
+        // 强调文字中以完整单词出现的验证码才算被强调，"12" 不会匹配 "123456" 中的一段
+        let emphasized: Vec<MappedText> = message
+            .emphasized
+            .iter()
+            .map(|text| MappedText::lowercase(&MappedText::normalize(text).text))
+            .collect();
+        let is_emphasized = |code: &str, formatted: &str| {
+            emphasized.iter().any(|text| {
+                [code, formatted]
+                    .iter()
+                    .any(|c| !keywords::find_word(text, &c.to_lowercase()).is_empty())
+            })
+        };
+
+        if is_emphasized(&result.code, &result.formatted) {
+            return Some(Extraction::Code(Box::new(ExtractionResult {
+                confidence: EMPHASIS_CONFIDENCE,
+                ..result
+            })));
+        }
+
+        let promoted = result
+            .alternates
+            .iter()
+            .find(|alternate| is_emphasized(&alternate.code, &alternate.formatted))?
+            .clone();
+        let previous = LabeledCode {
+            code: result.code.clone(),
+            formatted: result.formatted.clone(),
+            span: result.span,
+            role: result.role,
+            confidence: result.confidence,
+        };
+        let alternates = std::iter::once(previous)
+            .chain(
+                result
+                    .alternates
+                    .iter()
+                    .filter(|alternate| alternate.code != promoted.code)
+                    .cloned(),
+            )
+            .collect();
+        Some(Extraction::Code(Box::new(ExtractionResult {
+            code: promoted.code,
+            formatted: promoted.formatted,
+            span: promoted.span,
+            role: promoted.role,
+            confidence: 1.0 - (1.0 - promoted.confidence) * (1.0 - EMPHASIS_CONFIDENCE),
+            alternates,
+            ..result
+        })))
+    }
+}
+
+/// 一键登录链接，只识别域名在白名单内的链接
+pub struct SignInLinkDetector {
+    options: SignInLinkOptions,
+}
+
+impl SignInLinkDetector {
+    pub fn new(options: &SignInLinkOptions) -> Self {
+        Self {
+            options: options.clone(),
+        }
+    }
+}
+
+impl CodeExtractor for SignInLinkDetector {
+    fn name(&self) -> &str {
+        SIGN_IN_LINK
+    }
+
+    fn yields_codes(&self) -> bool {
+        false
+    }
+
+    fn extract(&self, message: &Message<'_>) -> Option<Extraction> {
+        if !self.options.enabled {
+            return None;
+        }
+
+        let normalized = MappedText::normalize(message.content);
+        let link = links::find_sign_in_link(&normalized.text, &self.options.allowed_domains)?;
+        Some(Extraction::SignInLink(SignInLink {
+            span: normalized.original_span(link.span),
+            ..link
+        }))
+    }
+}
+
+/// 按名称构建内置策略，未知的名称返回 None
+pub fn builtin(
+    name: &str,
+    config: &Config,
+    pipeline: &Arc<KeywordProximityExtractor>,
+) -> Option<Box<dyn CodeExtractor>> {
+    let strategy: Box<dyn CodeExtractor> = match name {
+        LEARNED_TEMPLATE => Box::new(LearnedTemplateExtractor::new(
+            pipeline.clone(),
+            &config.rules,
+        )),
+        SENDER_RULE => Box::new(SenderRuleExtractor::new(pipeline.clone(), &config.rules)),
+        EMAIL_SUBJECT => Box::new(SubjectExtractor::new(pipeline.clone())),
+        KEYWORD_PROXIMITY => Box::new(pipeline.clone()),
+        HTML_EMPHASIS => Box::new(HtmlEmphasisExtractor::new(pipeline.clone())),
+        SIGN_IN_LINK => Box::new(SignInLinkDetector::new(&config.sign_in_links)),
+        _ => return None,
+    };
+    Some(strategy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ExtractorChain;
+
+    fn rule(name: &str, sender: &str, regex: &str, learned: bool) -> SenderRule {
+        SenderRule {
+            name: Some(name.to_string()),
+            sender: Some(sender.to_string()),
+            regex: Some(regex.to_string()),
+            group: Some(1),
+            keywords: Some(vec!["动态码".to_string()]),
+            learned,
+            ..SenderRule::default()
+        }
+    }
+
+    fn code(extraction: Option<Extraction>) -> Option<String> {
+        match extraction {
+            Some(Extraction::Code(result)) => Some(result.code),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_builtin_strategies_in_isolation() {
+        let config = Config::default();
+        let pipeline = Arc::new(KeywordProximityExtractor::new(&config));
+        let bank = "您尾号1234的卡正在进行网上支付，动态码 839201，请勿泄露";
+
+        // 关键词距离：全局关键词中没有 "动态码"，找不到锚点
+        let message = Message::new("您的验证码为 482913", None);
+        assert_eq!(code(pipeline.extract(&message)).as_deref(), Some("482913"));
+        assert!(pipeline.extract(&Message::new(bank, None)).is_none());
+
+        // 发件人规则：命中后即使没有提取到验证码也是决定性的
+        let rules = vec![rule("bank", "95588", r"动态码\D*(\d{6})", false)];
+        let strategy = SenderRuleExtractor::new(pipeline.clone(), &rules);
+        let message = Message::new(bank, Some("95588"));
+        let extraction = strategy.extract(&message);
+        assert!(strategy.is_decisive(&message, extraction.as_ref()));
+        assert_eq!(code(extraction).as_deref(), Some("839201"));
+        let message = Message::new("您的动态码已发送", Some("95588"));
+        assert!(strategy.extract(&message).is_none());
+        assert!(strategy.is_decisive(&message, None));
+        assert!(!strategy.is_decisive(&Message::new(bank, Some("10690")), None));
+
+        // 学习到的规则：依次尝试同一发件人的规则，手写规则被忽略
+        let rules = vec![
+            rule("learned:a", "95588", r"验证码\s*(\d{4})", true),
+            rule("learned:b", "95588", r"动态码\s*(\d{6})", true),
+            rule("bank", "95588", r"(\d{4})", false),
+        ];
+        let strategy = LearnedTemplateExtractor::new(pipeline.clone(), &rules);
+        let message = Message::new(bank, Some("95588"));
+        let extraction = strategy.extract(&message);
+        assert!(strategy.is_decisive(&message, extraction.as_ref()));
+        match extraction {
+            Some(Extraction::Code(result)) => {
+                assert_eq!(result.code, "839201");
+                assert_eq!(result.rule.as_deref(), Some("learned:b"));
+            }
+            other => panic!("unexpected extraction: {:?}", other),
+        }
+
+        // 登录链接
+        let detector = SignInLinkDetector::new(&config.sign_in_links);
+        let message = Message::new("Click to sign in: https://slack.com/z/abc123", None);
+        match detector.extract(&message) {
+            Some(Extraction::SignInLink(link)) => {
+                assert_eq!(link.url, "https://slack.com/z/abc123")
+            }
+            other => panic!("unexpected extraction: {:?}", other),
+        }
+
+        assert!(builtin("no_such_strategy", &config, &pipeline).is_none());
+        assert_eq!(
+            builtin(SIGN_IN_LINK, &config, &pipeline).map(|s| s.name().to_string()),
+            Some(SIGN_IN_LINK.to_string())
+        );
+    }
+
+    #[test]
+    fn test_html_emphasis_boosts_emphasized_code() {
+        let config = Config::default();
+        let chain = ExtractorChain::new(&config);
+        let strategy =
+            HtmlEmphasisExtractor::new(Arc::new(KeywordProximityExtractor::new(&config)));
+
+        // 被强调的验证码与关键词距离的结果合并，置信度提高
+        let github = "Here's your GitHub launch code, @octocat:\n39281047\nOrder ref 5520 1934 is unrelated.";
+        assert!(strategy.extract(&Message::new(github, None)).is_none());
+        let plain = chain.extract(github, None).unwrap();
+        let emphasized = vec!["39281047".to_string()];
+        let message = Message::new(github, None).with_emphasized(&emphasized);
+        let result = chain.extract_message(&message).unwrap();
+        assert_eq!(result.code, "39281047");
+        assert_eq!(result.strategies, vec![KEYWORD_PROXIMITY, HTML_EMPHASIS]);
+        assert!(result.confidence > plain.confidence);
+
+        // 验证码只是强调文字中的一段时不算
+        let content = "验证码 482913，取消码 771234";
+        let emphasized = vec!["99771234".to_string(), "48".to_string()];
+        let message = Message::new(content, None).with_emphasized(&emphasized);
+        let result = chain.extract_message(&message).unwrap();
+        assert_eq!(result.code, "482913");
+        assert_eq!(result.strategies, vec![KEYWORD_PROXIMITY]);
+
+        // 只有备选验证码被强调时提升为选中的验证码
+        let emphasized = vec!["771234".to_string()];
+        let message = Message::new(content, None).with_emphasized(&emphasized);
+        let result = chain.extract_message(&message).unwrap();
+        assert_eq!(result.code, "771234");
+        assert_eq!(result.alternates[0].code, "482913");
+    }
+
+    #[test]
+    fn test_subject_code_scores_higher() {
+        let config = Config::default();
+        let chain = ExtractorChain::new(&config);
+        let content = "482913 is your Facebook code\nHi, someone tried to log in. Reference number 771234, use the code above.";
+
+        let subject = "482913 is your Facebook code";
+        let message = Message::new(content, Some("Facebook <security@facebookmail.com>"))
+            .with_subject(subject);
+        let result = chain.extract_message(&message).unwrap();
+        assert_eq!(result.code, "482913");
+        assert_eq!(result.span, (0, 6));
+        assert_eq!(result.strategies[0], EMAIL_SUBJECT);
+        assert_eq!(result.service.as_deref(), Some("Facebook"));
+        let body_only = chain.extract(content, None).unwrap();
+        assert!(result.confidence > body_only.confidence);
+
+        // 不在消息开头的主题被忽略
+        let message = Message::new(content, None).with_subject("Your code");
+        assert!(message.subject.is_none());
+    }
+}
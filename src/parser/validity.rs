@@ -0,0 +1,118 @@
+use fancy_regex::Regex;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+// 数字部分：阿拉伯数字或"一"、"十五"、"两"这样的中文数字
+const NUMBER: &str = r"(\d{1,4}|[零一二两三四五六七八九十]{1,3})";
+
+// 时间单位，英文单位需要完整单词匹配
+const UNIT: &str =
+    r"(秒钟|秒|分钟|分|个小时|小时|天|(?:seconds?|secs?|minutes?|mins?|hours?|hrs?|days?)\b)";
+
+static VALIDITY_RES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        // "有效时间为一分钟"、"有效期10分钟"、"有效期：5分钟"
+        format!(r"有效(?:时间|期|时长)?\s*(?:为|是|:|：)?\s*{NUMBER}\s*{UNIT}"),
+        // "5分钟内有效"、"30秒后失效"
+        format!(r"{NUMBER}\s*{UNIT}\s*(?:内|之内|以内|后)?\s*(?:有效|失效|过期)"),
+        // "valid for 10 minutes"、"expires in 5 min"、"valid within the next 15 minutes"
+        format!(
+            r"(?i)\b(?:valid|expires?|expiring|good)\s+(?:for|in|within|after)\s+(?:the\s+next\s+)?{NUMBER}\s*{UNIT}"
+        ),
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// 提取消息中声明的有效期，例如 "5分钟内有效"、"valid for 10 minutes"
+pub fn find_validity(text: &str) -> Option<Duration> {
+    VALIDITY_RES
+        .iter()
+        .filter_map(|re| re.captures(text).ok().flatten())
+        .min_by_key(|captures| captures.get(0).map_or(usize::MAX, |m| m.start()))
+        .and_then(|captures| {
+            let amount = parse_number(captures.get(1)?.as_str())?;
+            let unit = unit_seconds(&captures.get(2)?.as_str().to_lowercase())?;
+            (amount > 0).then(|| Duration::from_secs(amount * unit))
+        })
+}
+
+fn unit_seconds(unit: &str) -> Option<u64> {
+    match unit {
+        "秒钟" | "秒" | "second" | "seconds" | "sec" | "secs" => Some(1),
+        "分钟" | "分" | "minute" | "minutes" | "min" | "mins" => Some(60),
+        "个小时" | "小时" | "hour" | "hours" | "hr" | "hrs" => Some(3600),
+        "天" | "day" | "days" => Some(86400),
+        _ => None,
+    }
+}
+
+fn parse_number(text: &str) -> Option<u64> {
+    if let Ok(number) = text.parse() {
+        return Some(number);
+    }
+
+    // 中文数字，只处理 0 ~ 99："十"、"十五"、"三十"、"二十五"
+    let digit = |c: char| match c {
+        '零' => Some(0),
+        '一' => Some(1),
+        '二' | '两' => Some(2),
+        '三' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        _ => None,
+    };
+    let chars: Vec<char> = text.chars().collect();
+    match chars.iter().position(|&c| c == '十') {
+        None if chars.len() == 1 => digit(chars[0]),
+        None => None,
+        Some(pos) => {
+            let tens = match &chars[..pos] {
+                [] => 1,
+                [c] => digit(*c)?,
+                _ => return None,
+            };
+            let ones = match &chars[pos + 1..] {
+                [] => 0,
+                [c] => digit(*c)?,
+                _ => return None,
+            };
+            Some(tens * 10 + ones)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validity_in_chinese_and_english() {
+        let minutes = |m: u64| Some(Duration::from_secs(m * 60));
+        let cases = vec![
+            ("自如验证码 356407，有效时间为一分钟，请勿泄露", minutes(1)),
+            ("验证码 482913，5分钟内有效", minutes(5)),
+            ("验证码 482913，有效期：十五分钟", minutes(15)),
+            ("验证码 482913，三十秒后失效", Some(Duration::from_secs(30))),
+            (
+                "Your code is 482913. It is valid for 10 minutes.",
+                minutes(10),
+            ),
+            (
+                "Code 482913 expires in 2 hours",
+                Some(Duration::from_secs(7200)),
+            ),
+            ("Your code is 482913", None),
+            ("Sent 5 minutes ago", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(find_validity(input), expected, "input: {:?}", input);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::Config;
+use crate::history::PickupHistory;
 use crate::launch::LaunchManager;
 use crate::monitor::commands::MonitorCommand;
 use crate::updater;
@@ -15,6 +16,14 @@ use tray_icon::{
 };
 use winit::{application::ApplicationHandler, event_loop::EventLoop};
 
+// 监听器最近识别到的一键登录链接，点击托盘菜单"打开链接"后清空
+static LAST_SIGN_IN_LINK: Mutex<Option<String>> = Mutex::new(None);
+
+/// 记录新识别到的登录链接，托盘菜单下次展开时可以打开它
+pub fn set_sign_in_link(url: &str) {
+    *LAST_SIGN_IN_LINK.lock().unwrap() = Some(url.to_string());
+}
+
 #[derive(Debug)]
 pub enum UserEvent {
     TrayIconEvent(tray_icon::TrayIconEvent),
@@ -40,6 +49,8 @@ struct MenuItems {
     listen_email: CheckMenuItem,
     listen_message: CheckMenuItem,
     floating_window: CheckMenuItem,
+    open_link: MenuItem,
+    pickup_history: MenuItem,
     config: MenuItem,
     log: MenuItem,
     check_update: MenuItem,
@@ -200,6 +211,8 @@ impl TrayApplication {
                 config_guard.floating_window,
                 None,
             ),
+            open_link: MenuItem::new(&t!("menu.open_link"), false, None),
+            pickup_history: MenuItem::new(&t!("menu.pickup_history"), true, None),
             config: MenuItem::new(&t!("menu.config"), true, None),
             log: MenuItem::new(&t!("menu.log"), true, None),
             check_update: MenuItem::new(&t!("menu.check_update"), true, None),
@@ -217,6 +230,15 @@ impl TrayApplication {
         // 应用互斥逻辑
         self.apply_menu_logic(items_ref, &config_guard);
 
+        // 配置中的正则无效时，在菜单顶部显示一条不可点击的提示
+        if let Some(error) = &config_guard.regex_error {
+            let error = error.lines().next().unwrap_or_default();
+            let warning =
+                MenuItem::new(&t!("tray.invalid_regex_warning", error = error), false, None);
+            menu.append(&warning)?;
+            menu.append(&PredefinedMenuItem::separator())?;
+        }
+
         // 使用 items_ref 中的引用来构建菜单
         menu.append(&items_ref.auto_paste)?;
         menu.append(&items_ref.auto_enter)?;
@@ -228,6 +250,9 @@ impl TrayApplication {
         menu.append(&items_ref.launch_at_login)?;
         menu.append(&items_ref.floating_window)?;
         menu.append(&PredefinedMenuItem::separator())?;
+        menu.append(&items_ref.open_link)?;
+        menu.append(&items_ref.pickup_history)?;
+        menu.append(&PredefinedMenuItem::separator())?;
         menu.append(&items_ref.config)?;
         menu.append(&items_ref.log)?;
         menu.append(&items_ref.check_update)?;
@@ -258,6 +283,14 @@ impl TrayApplication {
         }
         // auto_enter 不受其他配置影响，始终保持可用状态
     }
+
+    // 有待打开的登录链接时才启用"打开链接"菜单项
+    fn refresh_open_link(&self) {
+        if let Some(menu_items) = &self.menu_items {
+            let has_link = LAST_SIGN_IN_LINK.lock().unwrap().is_some();
+            menu_items.open_link.set_enabled(has_link);
+        }
+    }
 }
 
 impl ApplicationHandler<UserEvent> for TrayApplication {
@@ -306,6 +339,8 @@ impl ApplicationHandler<UserEvent> for TrayApplication {
         match event {
             UserEvent::TrayIconEvent(_event) => {
                 // debug!("Tray event: {:?}", event); // 注释掉，太吵闹了
+                // 鼠标移入或点击托盘图标时，菜单弹出前刷新链接状态
+                self.refresh_open_link();
             }
             UserEvent::MenuEvent(event) => {
                 if let Some(menu_items) = &self.menu_items {
@@ -459,6 +494,48 @@ impl ApplicationHandler<UserEvent> for TrayApplication {
 
                         // 重新应用菜单逻辑
                         self.apply_menu_logic(menu_items, &config);
+                    } else if event.id == menu_items.open_link.id() {
+                        // 登录链接只在用户点击后打开，打开后清空
+                        if let Some(url) = LAST_SIGN_IN_LINK.lock().unwrap().take() {
+                            #[cfg(target_os = "macos")]
+                            {
+                                use std::process::Command;
+                                if let Err(e) = Command::new("open").arg(&url).output() {
+                                    log::error!("Failed to open sign-in link: {}", e);
+                                } else {
+                                    info!("Opened sign-in link: {}", url);
+                                }
+                            }
+                            #[cfg(not(target_os = "macos"))]
+                            {
+                                log::warn!("Opening links is only supported on macOS: {}", url);
+                            }
+                        }
+                        menu_items.open_link.set_enabled(false);
+                    } else if event.id == menu_items.pickup_history.id() {
+                        // pickup history menu item - open pickup code history file
+                        let history_path = PickupHistory::get_history_path();
+                        #[cfg(target_os = "macos")]
+                        {
+                            use std::process::Command;
+                            if !history_path.exists() {
+                                if let Err(e) = PickupHistory::default().save_to(&history_path) {
+                                    log::error!("Failed to create pickup code history: {}", e);
+                                }
+                            }
+                            if let Err(e) = Command::new("open").arg(&history_path).output() {
+                                log::error!("Failed to open pickup code history: {}", e);
+                            } else {
+                                info!("Opened pickup code history: {:?}", history_path);
+                            }
+                        }
+                        #[cfg(not(target_os = "macos"))]
+                        {
+                            log::warn!(
+                                "Pickup code history opening is only supported on macOS: {:?}",
+                                history_path
+                            );
+                        }
                     } else if event.id == menu_items.config.id() {
                         // config menu item - open config file
                         let config_path = Config::get_config_path();
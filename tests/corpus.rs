@@ -0,0 +1,24 @@
+// 回归语料的正确率测试：用默认配置逐条运行 tests/corpus 中的样本，
+// 未标记 known_failure 的样本结果与期望不符时失败
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_regression_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let output = Command::new(env!("CARGO_BIN_EXE_messauto"))
+        .arg("--corpus-check")
+        .arg(&dir)
+        .output()
+        .expect("failed to run messauto");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    println!("{}", stdout);
+    assert!(
+        output.status.success(),
+        "corpus check failed:\n{}\n{}",
+        stdout,
+        stderr
+    );
+}